@@ -1,8 +1,9 @@
-use std::path::PathBuf;
+use needletail::FastxReader;
+use std::path::{Path, PathBuf};
 use structopt::StructOpt;
 use thiserror::Error;
 
-/// Read filters and summary reports for nanopore data
+/// Read filters, summary reports, and subsampling for nanopore data
 #[derive(Debug, StructOpt)]
 #[structopt()]
 pub struct Cli {
@@ -14,12 +15,104 @@ pub struct Cli {
     #[structopt(short, long, parse(from_os_str))]
     pub output: Option<PathBuf>,
 
-    /// Minimum read length filter (bp).
-    #[structopt(short = "l", long, value_name = "INT", default_value = "0")]
+    /// u: uncompressed; b: Bzip2; g: Gzip; l: Lzma; f: BGZF (multithreaded, see --threads); z: Zstandard; s: Snappy
+    ///
+    /// Nanoq will attempt to infer the output compression format automatically
+    /// from the filename extension. This option is used to override that.
+    /// If writing to stdout, the default is uncompressed
+    #[structopt(
+        short = "O",
+        long,
+        value_name = "u|b|g|l|f|z|s",
+        parse(try_from_str = parse_compression_format),
+        possible_values = &["u", "b", "g", "l", "f", "z", "s"],
+        case_insensitive = true,
+        hide_possible_values = true
+    )]
+    pub output_type: Option<OutputFormat>,
+
+    /// Compression level to use if compressing output.
+    #[structopt(
+        short = "c",
+        long,
+        parse(try_from_str = parse_compression_level),
+        default_value="6",
+        value_name = "1-9"
+    )]
+    pub compress_level: niffler::Level,
+
+    /// Number of threads for multithreaded BGZF output (`-O f`)
+    ///
+    /// Ignored for all other output formats, which are always single-threaded.
+    /// `0` (the default) uses all available logical CPUs; `1` falls back to
+    /// the single-threaded Gzip codec instead of spinning up a thread pool.
+    #[structopt(short = "j", long, default_value = "0", value_name = "INT")]
+    pub threads: usize,
+
+    /// Run `--input` through an external command instead of opening it directly
+    ///
+    /// Lets `--input` point at archives niffler can't read natively (`.zip`,
+    /// `.sra`, ...): the command is spawned with the input path appended as
+    /// its final argument and its stdout is read as the FASTX stream, e.g.
+    /// `--preprocessor "zstd -dc"`. A handful of known extensions (`zip`,
+    /// `tar`, `tgz`, `sra`) get a sensible default command if this is not given.
+    #[structopt(long, value_name = "CMD")]
+    pub preprocessor: Option<String>,
+
+    #[structopt(subcommand)]
+    pub command: Command,
+}
+
+/// Output compression format
+///
+/// Wraps niffler's single-threaded codecs and adds formats niffler does not
+/// cover: multithreaded block-gzip (BGZF, backed by the `gzp` crate) and the
+/// `zstd`/`snap` frame encoders, all common in modern bioinformatics pipelines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// One of niffler's single-threaded codecs (Gzip, Bzip, Lzma, or none)
+    Niffler(niffler::compression::Format),
+    /// Multithreaded block-gzip, split into independently-deflated blocks
+    /// across a thread pool; output remains standard `bgzip`/tabix-indexable `.gz`
+    Bgzf,
+    /// Zstandard, honoring `--compress-level` (mapped 1-9 onto zstd's levels)
+    Zstd,
+    /// Snappy framing format; `--compress-level` has no effect here
+    Snappy,
+}
+
+/// Subcommands sharing the parent `input`/`output`/compression options above
+#[derive(Debug, StructOpt)]
+pub enum Command {
+    /// Filter reads by length, quality, and GC content
+    Filter(FilterArgs),
+    /// Compute a summary report of read length/quality statistics
+    Stats(StatsArgs),
+    /// Randomly or selectively subsample reads
+    Sample(SampleArgs),
+}
+
+/// Arguments for the `filter` subcommand
+#[derive(Debug, StructOpt)]
+pub struct FilterArgs {
+    /// Minimum read length filter (bp), accepts metric suffixes e.g. `5kb`, `2.5m`.
+    #[structopt(
+        short = "l",
+        long,
+        value_name = "INT",
+        default_value = "0",
+        parse(try_from_str = parse_size_u32)
+    )]
     pub min_len: u32,
 
-    /// Maximum read length filter (bp).
-    #[structopt(short = "m", long, value_name = "INT", default_value = "0")]
+    /// Maximum read length filter (bp), accepts metric suffixes e.g. `5kb`, `2.5m`.
+    #[structopt(
+        short = "m",
+        long,
+        value_name = "INT",
+        default_value = "0",
+        parse(try_from_str = parse_size_u32)
+    )]
     pub max_len: u32,
 
     /// Minimum average read quality filter (Q).
@@ -30,7 +123,35 @@ pub struct Cli {
     #[structopt(short = "w", long, value_name = "FLOAT", default_value = "0")]
     pub max_qual: f32,
 
-    /// Verbose output statistics [multiple, up to -vvv]  
+    /// Minimum GC content filter (fraction, 0.0-1.0).
+    #[structopt(long, value_name = "FLOAT", default_value = "0")]
+    pub min_gc: f32,
+
+    /// Maximum GC content filter (fraction, 0.0-1.0).
+    #[structopt(long, value_name = "FLOAT", default_value = "0")]
+    pub max_gc: f32,
+
+    /// Ignore quality values if present.
+    #[structopt(short, long)]
+    pub fast: bool,
+
+    /// Number of bases to trim from the start of each read.
+    #[structopt(long = "trim-start", value_name = "INT", default_value = "0")]
+    pub trim_start: usize,
+
+    /// Number of bases to trim from the end of each read.
+    #[structopt(long = "trim-end", value_name = "INT", default_value = "0")]
+    pub trim_end: usize,
+
+    /// Per-read report (read_id, length, mean quality) for reads surviving `filter`.
+    #[structopt(long = "per-read", value_name = "PATH", parse(from_os_str))]
+    pub per_read: Option<PathBuf>,
+}
+
+/// Arguments for the `stats` subcommand
+#[derive(Debug, StructOpt)]
+pub struct StatsArgs {
+    /// Verbose output statistics [multiple, up to -vvv]
     #[structopt(
         short,
         long,
@@ -42,7 +163,7 @@ pub struct Cli {
     #[structopt(short = "H", long)]
     pub header: bool,
 
-    /// Number of top reads in verbose summary.  
+    /// Number of top reads in verbose summary.
     #[structopt(short, long, value_name = "INT", default_value = "5")]
     pub top: usize,
 
@@ -66,35 +187,80 @@ pub struct Cli {
     #[structopt(short, long)]
     pub read_qualities: Option<PathBuf>,
 
-    /// Ignore quality values if present.
-    #[structopt(short, long)]
-    pub fast: bool,
+    /// Use approximate statistics (binned histograms) instead of exact computations. Read lengths and qualities are still collected in memory before binning.
+    #[structopt(long)]
+    pub stream: bool,
 
-    /// u: uncompressed; b: Bzip2; g: Gzip; l: Lzma
-    ///
-    /// Nanoq will attempt to infer the output compression format automatically
-    /// from the filename extension. This option is used to override that.
-    /// If writing to stdout, the default is uncompressed
+    /// Use the P² algorithm for approximate median length/quality estimation. Read lengths and qualities are still collected in memory before estimation.
+    #[structopt(long)]
+    pub p2: bool,
+
+    /// Comma-separated read length thresholds (bp) for the verbosity-2 report, accepts metric suffixes e.g. `5kb`. Defaults to a built-in progression.
     #[structopt(
-        short = "O", 
         long,
-        value_name = "u|b|g|l", 
-        parse(try_from_str = parse_compression_format),
-        possible_values = &["u", "b", "g", "l"], 
-        case_insensitive = true,
-        hide_possible_values = true
+        value_name = "INT,...",
+        use_delimiter = true,
+        parse(try_from_str = parse_size)
     )]
-    pub output_type: Option<niffler::compression::Format>,
+    pub length_thresholds: Vec<u64>,
 
-    /// Compression level to use if compressing output.
+    /// Comma-separated read quality thresholds (Q) for the verbosity-2 report. Defaults to a built-in progression.
+    #[structopt(long, value_name = "INT,...", use_delimiter = true)]
+    pub quality_thresholds: Vec<u64>,
+
+    /// Comma-separated percentiles (0-100) of read length/quality for the verbosity-2 report. Defaults to a built-in progression.
+    #[structopt(long, value_name = "INT,...", use_delimiter = true)]
+    pub percentiles: Vec<u64>,
+
+    /// Comma-separated Nx/Lx percentages (e.g. `50,90`) for the verbosity-2 report, also used for NGx when `--genome-size` is given. Defaults to 10,50,90.
+    #[structopt(long = "nx", value_name = "INT,...", use_delimiter = true)]
+    pub nx_percentages: Vec<u64>,
+
+    /// Genome size (bp) used to compute NGx alongside Nx/Lx, accepts metric suffixes e.g. `5mb` or a FASTA/FAI path.
+    #[structopt(long, value_name = "INT|PATH", parse(try_from_str = parse_genome_size))]
+    pub genome_size: Option<u64>,
+}
+
+/// Arguments for the `sample` subcommand
+#[derive(Debug, StructOpt)]
+pub struct SampleArgs {
+    /// Randomly subsample to this many reads (reservoir sampling).
+    #[structopt(long, value_name = "INT")]
+    pub sample_reads: Option<usize>,
+
+    /// Randomly subsample to approximately this depth of coverage, requires `--genome-size`.
+    #[structopt(long, value_name = "FLOAT", requires = "genome-size")]
+    pub coverage: Option<f64>,
+
+    /// Genome size (bp) used to compute the target base count for `--coverage`, accepts metric suffixes e.g. `5mb` or a FASTA/FAI path.
+    #[structopt(long, value_name = "INT|PATH", parse(try_from_str = parse_genome_size))]
+    pub genome_size: Option<u64>,
+
+    /// Randomly subsample to approximately this many total bases, requires a seekable input file. Accepts metric suffixes e.g. `1.5gb`.
+    #[structopt(long, value_name = "INT", parse(try_from_str = parse_size))]
+    pub sample_bases: Option<u64>,
+
+    /// Downsample to the best reads up to this many retained bases, requires a seekable input file. Accepts metric suffixes e.g. `500m`.
+    #[structopt(long, value_name = "INT", parse(try_from_str = parse_size))]
+    pub target_bases: Option<u64>,
+
+    /// Randomly subsample by independently keeping each read with this probability (0.0-1.0). Single-pass, so it also works on unseekable input such as stdin.
+    #[structopt(long, value_name = "FLOAT")]
+    pub fraction: Option<f64>,
+
+    /// Ranking key used to select reads for `--target-bases`: `length` or `quality`.
     #[structopt(
-        short = "c", 
         long,
-        parse(try_from_str = parse_compression_level),
-        default_value="6", 
-        value_name = "1-9"
+        value_name = "length|quality",
+        default_value = "length",
+        possible_values = &["length", "quality"],
+        case_insensitive = true
     )]
-    pub compress_level: niffler::Level,
+    pub target_by: String,
+
+    /// Seed for the random number generator used by `--sample-reads`, `--coverage`, `--sample-bases` and `--fraction`.
+    #[structopt(long, value_name = "INT", default_value = "42")]
+    pub seed: u64,
 }
 
 /// A collection of custom errors relating to the command line interface for this package.
@@ -107,6 +273,15 @@ pub enum CliError {
     /// Indicates that a string cannot be parsed into a [`CompressionLevel`](#compressionlevel).
     #[error("{0} is not a valid compression level [1-9]")]
     InvalidCompressionLevel(String),
+
+    /// Indicates that a size argument has an unrecognised metric suffix.
+    #[error("{0} is not a valid size: unknown suffix, expected one of k, m, g, t (optionally followed by 'b')")]
+    InvalidSizeUnit(String),
+
+    /// Indicates that a size argument's numeric part could not be parsed,
+    /// or that a genome-size path could not be read as FASTA/FAI.
+    #[error("{0} is not a valid size: could not parse the numeric part or read it as a FASTA/FAI path")]
+    InvalidSizeNumber(String),
 }
 
 /// Utility function to parse verbosity occurences
@@ -120,13 +295,130 @@ pub fn parse_verbosity(v: u64) -> u64 {
     }
 }
 
+/// Utility function to parse a human-readable size with an optional metric suffix
+///
+/// Accepts plain integers (`500`) as well as values with a `k`/`m`/`g`/`t`
+/// suffix (case-insensitive, with an optional trailing `b`), multiplying
+/// the numeric part by 1000/1_000_000/1_000_000_000/1_000_000_000_000
+/// respectively. The numeric part is parsed as `f64` so decimal magnitudes
+/// such as `1.5gb` are accepted.
+pub fn parse_size(s: &str) -> Result<u64, CliError> {
+    let split_at = s
+        .find(|c: char| c.is_ascii_alphabetic())
+        .unwrap_or(s.len());
+    let (number, suffix) = s.split_at(split_at);
+
+    let number: f64 = number
+        .parse()
+        .map_err(|_| CliError::InvalidSizeNumber(s.to_string()))?;
+
+    if number < 0.0 {
+        return Err(CliError::InvalidSizeNumber(s.to_string()));
+    }
+
+    let suffix = suffix.to_lowercase();
+    let suffix = suffix.strip_suffix('b').unwrap_or(&suffix);
+
+    let multiplier = match suffix {
+        "" => 1f64,
+        "k" => 1_000f64,
+        "m" => 1_000_000f64,
+        "g" => 1_000_000_000f64,
+        "t" => 1_000_000_000_000f64,
+        _ => return Err(CliError::InvalidSizeUnit(s.to_string())),
+    };
+
+    Ok((number * multiplier).round() as u64)
+}
+
+/// Utility function to parse a human-readable size into a `u32`
+///
+/// Used for arguments such as `--min-len`/`--max-len` that are stored as
+/// `u32`; delegates to [`parse_size`] and rejects magnitudes that overflow.
+fn parse_size_u32(s: &str) -> Result<u32, CliError> {
+    let value = parse_size(s)?;
+    u32::try_from(value).map_err(|_| CliError::InvalidSizeNumber(s.to_string()))
+}
+
+/// A genome size in bases, parsed from a metric-suffixed magnitude or a FASTA/FAI path
+///
+/// Used for `--genome-size`: accepts the same suffixes as [`parse_size`]
+/// (`b`/`kb`/`Mb`/`Gb`/`Tb`, case-insensitive). As an alternative to a
+/// literal magnitude, the value may instead be a path to a FASTA file or
+/// its `.fai` index, in which case the genome size is computed as the sum
+/// of all reference sequence lengths.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GenomeSize(pub u64);
+
+impl std::str::FromStr for GenomeSize {
+    type Err = CliError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(bases) = parse_size(s) {
+            return Ok(GenomeSize(bases));
+        }
+        genome_size_from_path(Path::new(s)).map(GenomeSize)
+    }
+}
+
+/// Utility function to parse `--genome-size`, keeping the CLI field plain `u64`
+fn parse_genome_size(s: &str) -> Result<u64, CliError> {
+    s.parse::<GenomeSize>().map(|size| size.0)
+}
+
+/// Sum reference sequence lengths from a FASTA file or its `.fai` index
+///
+/// A `.fai` index (tab-separated `name\tlength\t...`) is used directly if
+/// present alongside `path`, since summing its second column is far
+/// cheaper than re-parsing the sequences; otherwise `path` itself is read
+/// as FASTA/FASTQ and each record's length is summed.
+fn genome_size_from_path(path: &Path) -> Result<u64, CliError> {
+    let invalid = || CliError::InvalidSizeNumber(path.display().to_string());
+
+    let is_fai = path.extension().map(|ext| ext == "fai").unwrap_or(false);
+    let fai_path = if is_fai {
+        path.to_path_buf()
+    } else {
+        let mut fai_name = path.file_name().ok_or_else(invalid)?.to_os_string();
+        fai_name.push(".fai");
+        path.with_file_name(fai_name)
+    };
+
+    if fai_path.is_file() {
+        let contents = std::fs::read_to_string(&fai_path).map_err(|_| invalid())?;
+        return contents
+            .lines()
+            .map(|line| {
+                line.split('\t')
+                    .nth(1)
+                    .and_then(|length| length.parse::<u64>().ok())
+                    .ok_or_else(invalid)
+            })
+            .sum();
+    }
+
+    if !path.is_file() {
+        return Err(invalid());
+    }
+
+    let mut reader = needletail::parse_fastx_file(path).map_err(|_| invalid())?;
+    let mut total: u64 = 0;
+    while let Some(record) = reader.next() {
+        total += record.map_err(|_| invalid())?.num_bases() as u64;
+    }
+    Ok(total)
+}
+
 /// Utility function to parse compression format
-fn parse_compression_format(s: &str) -> Result<niffler::compression::Format, CliError> {
+fn parse_compression_format(s: &str) -> Result<OutputFormat, CliError> {
     match s {
-        "b" | "B" => Ok(niffler::Format::Bzip),
-        "g" | "G" => Ok(niffler::Format::Gzip),
-        "l" | "L" => Ok(niffler::Format::Lzma),
-        "u" | "U" => Ok(niffler::Format::No),
+        "b" | "B" => Ok(OutputFormat::Niffler(niffler::Format::Bzip)),
+        "g" | "G" => Ok(OutputFormat::Niffler(niffler::Format::Gzip)),
+        "l" | "L" => Ok(OutputFormat::Niffler(niffler::Format::Lzma)),
+        "u" | "U" => Ok(OutputFormat::Niffler(niffler::Format::No)),
+        "f" | "F" => Ok(OutputFormat::Bgzf),
+        "z" | "Z" => Ok(OutputFormat::Zstd),
+        "s" | "S" => Ok(OutputFormat::Snappy),
         _ => Err(CliError::InvalidCompressionFormat(s.to_string())),
     }
 }
@@ -153,10 +445,13 @@ fn parse_compression_level(s: &str) -> Result<niffler::Level, CliError> {
 mod tests {
     use super::*;
 
+    fn parse(args: Vec<&str>) -> Result<Cli, clap::Error> {
+        Cli::from_iter_safe(args)
+    }
+
     #[test]
     fn invalid_compression_format() {
-        let passed_args = vec!["nanoq", "-O", "t"];
-        let args: Result<Cli, clap::Error> = Cli::from_iter_safe(passed_args);
+        let args = parse(vec!["nanoq", "-O", "t", "filter"]);
 
         let actual = args.unwrap_err().kind;
         let expected = clap::ErrorKind::InvalidValue;
@@ -166,8 +461,7 @@ mod tests {
 
     #[test]
     fn invalid_compression_level() {
-        let passed_args = vec!["nanoq", "-c", "10"];
-        let args: Result<Cli, clap::Error> = Cli::from_iter_safe(passed_args);
+        let args = parse(vec!["nanoq", "-c", "10", "filter"]);
 
         let actual = args.unwrap_err().kind;
         let expected = clap::ErrorKind::ValueValidation;
@@ -177,19 +471,18 @@ mod tests {
 
     #[test]
     fn verbosity_exceeds_limit() {
-        let passed_args = vec!["nanoq", "-vvvv"];
-        let args = Cli::from_iter_safe(passed_args);
+        let args = parse(vec!["nanoq", "stats", "-vvvv"]);
 
-        let actual = args.unwrap().verbose;
-        let expected = 3;
-
-        assert_eq!(actual, expected)
+        let actual = args.unwrap().command;
+        match actual {
+            Command::Stats(stats) => assert_eq!(stats.verbose, 3),
+            _ => panic!("expected the stats subcommand"),
+        }
     }
 
     #[test]
     fn invalid_min_len() {
-        let passed_args = vec!["nanoq", "-l", "test"];
-        let args: Result<Cli, clap::Error> = Cli::from_iter_safe(passed_args);
+        let args = parse(vec!["nanoq", "filter", "-l", "test"]);
 
         let actual = args.unwrap_err().kind;
         let expected = clap::ErrorKind::ValueValidation;
@@ -199,8 +492,7 @@ mod tests {
 
     #[test]
     fn invalid_max_len() {
-        let passed_args = vec!["nanoq", "-m", "test"];
-        let args: Result<Cli, clap::Error> = Cli::from_iter_safe(passed_args);
+        let args = parse(vec!["nanoq", "filter", "-m", "test"]);
 
         let actual = args.unwrap_err().kind;
         let expected = clap::ErrorKind::ValueValidation;
@@ -210,8 +502,7 @@ mod tests {
 
     #[test]
     fn invalid_min_qual() {
-        let passed_args = vec!["nanoq", "-q", "test"];
-        let args: Result<Cli, clap::Error> = Cli::from_iter_safe(passed_args);
+        let args = parse(vec!["nanoq", "filter", "-q", "test"]);
 
         let actual = args.unwrap_err().kind;
         let expected = clap::ErrorKind::ValueValidation;
@@ -221,8 +512,7 @@ mod tests {
 
     #[test]
     fn invalid_max_qual() {
-        let passed_args = vec!["nanoq", "-w", "test"];
-        let args: Result<Cli, clap::Error> = Cli::from_iter_safe(passed_args);
+        let args = parse(vec!["nanoq", "filter", "-w", "test"]);
 
         let actual = args.unwrap_err().kind;
         let expected = clap::ErrorKind::ValueValidation;
@@ -231,9 +521,8 @@ mod tests {
     }
 
     #[test]
-    fn invalid_to_value() {
-        let passed_args = vec!["nanoq", "-t", "test"];
-        let args: Result<Cli, clap::Error> = Cli::from_iter_safe(passed_args);
+    fn invalid_top_value() {
+        let args = parse(vec!["nanoq", "stats", "-t", "test"]);
 
         let actual = args.unwrap_err().kind;
         let expected = clap::ErrorKind::ValueValidation;
@@ -243,35 +532,38 @@ mod tests {
 
     #[test]
     fn valid_stats_flag() {
-        let passed_args = vec!["nanoq", "-s"];
-        let args: Result<Cli, clap::Error> = Cli::from_iter_safe(passed_args);
+        let args = parse(vec!["nanoq", "stats", "-s"]);
 
-        let actual = args.unwrap().stats;
-        let expected = true;
+        let actual = match args.unwrap().command {
+            Command::Stats(stats) => stats.stats,
+            _ => panic!("expected the stats subcommand"),
+        };
 
-        assert_eq!(actual, expected)
+        assert!(actual)
     }
 
     #[test]
     fn valid_fast_flag() {
-        let passed_args = vec!["nanoq", "-f"];
-        let args: Result<Cli, clap::Error> = Cli::from_iter_safe(passed_args);
+        let args = parse(vec!["nanoq", "filter", "-f"]);
 
-        let actual = args.unwrap().fast;
-        let expected = true;
+        let actual = match args.unwrap().command {
+            Command::Filter(filter) => filter.fast,
+            _ => panic!("expected the filter subcommand"),
+        };
 
-        assert_eq!(actual, expected)
+        assert!(actual)
     }
 
     #[test]
     fn valid_verbosity_level() {
-        let passed_args = vec!["nanoq", "-vv"];
-        let args = Cli::from_iter_safe(passed_args);
+        let args = parse(vec!["nanoq", "stats", "-vv"]);
 
-        let actual = args.unwrap().verbose;
-        let expected = 2;
+        let actual = match args.unwrap().command {
+            Command::Stats(stats) => stats.verbose,
+            _ => panic!("expected the stats subcommand"),
+        };
 
-        assert_eq!(actual, expected)
+        assert_eq!(actual, 2)
     }
 
     #[test]
@@ -287,16 +579,37 @@ mod tests {
     #[test]
     fn compression_format_from_str() {
         let mut s = "B";
-        assert_eq!(parse_compression_format(s).unwrap(), niffler::Format::Bzip);
+        assert_eq!(
+            parse_compression_format(s).unwrap(),
+            OutputFormat::Niffler(niffler::Format::Bzip)
+        );
 
         s = "g";
-        assert_eq!(parse_compression_format(s).unwrap(), niffler::Format::Gzip);
+        assert_eq!(
+            parse_compression_format(s).unwrap(),
+            OutputFormat::Niffler(niffler::Format::Gzip)
+        );
 
         s = "l";
-        assert_eq!(parse_compression_format(s).unwrap(), niffler::Format::Lzma);
+        assert_eq!(
+            parse_compression_format(s).unwrap(),
+            OutputFormat::Niffler(niffler::Format::Lzma)
+        );
 
         s = "U";
-        assert_eq!(parse_compression_format(s).unwrap(), niffler::Format::No);
+        assert_eq!(
+            parse_compression_format(s).unwrap(),
+            OutputFormat::Niffler(niffler::Format::No)
+        );
+
+        s = "f";
+        assert_eq!(parse_compression_format(s).unwrap(), OutputFormat::Bgzf);
+
+        s = "z";
+        assert_eq!(parse_compression_format(s).unwrap(), OutputFormat::Zstd);
+
+        s = "S";
+        assert_eq!(parse_compression_format(s).unwrap(), OutputFormat::Snappy);
 
         s = "a";
         assert_eq!(
@@ -305,6 +618,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn threads_defaults_to_zero_meaning_auto() {
+        let args = parse(vec!["nanoq", "filter"]);
+        assert_eq!(args.unwrap().threads, 0);
+    }
+
+    #[test]
+    fn threads_parses_explicit_value() {
+        let args = parse(vec!["nanoq", "-j", "4", "filter"]);
+        assert_eq!(args.unwrap().threads, 4);
+    }
+
+    #[test]
+    fn preprocessor_defaults_to_none() {
+        let args = parse(vec!["nanoq", "filter"]);
+        assert_eq!(args.unwrap().preprocessor, None);
+    }
+
+    #[test]
+    fn preprocessor_parses_command() {
+        let args = parse(vec!["nanoq", "--preprocessor", "zstd -dc", "filter"]);
+        assert_eq!(args.unwrap().preprocessor, Some("zstd -dc".to_string()));
+    }
+
     #[test]
     fn compression_level_in_range() {
         assert!(parse_compression_level("1").is_ok());
@@ -322,4 +659,184 @@ mod tests {
         assert!(parse_compression_level("5.5").is_err());
         assert!(parse_compression_level("-3").is_err());
     }
+
+    #[test]
+    fn size_from_str_plain_and_suffixed() {
+        assert_eq!(parse_size("500").unwrap(), 500);
+        assert_eq!(parse_size("20k").unwrap(), 20_000);
+        assert_eq!(parse_size("5mb").unwrap(), 5_000_000);
+        assert_eq!(parse_size("2g").unwrap(), 2_000_000_000);
+        assert_eq!(parse_size("1.5gb").unwrap(), 1_500_000_000);
+        assert_eq!(parse_size("1.5GB").unwrap(), 1_500_000_000);
+        assert_eq!(parse_size("2tb").unwrap(), 2_000_000_000_000);
+    }
+
+    #[test]
+    fn size_from_str_rejects_unknown_suffix() {
+        assert_eq!(
+            parse_size("5xb").unwrap_err(),
+            CliError::InvalidSizeUnit("5xb".to_string())
+        );
+        assert_eq!(
+            parse_size("tb").unwrap_err(),
+            CliError::InvalidSizeNumber("tb".to_string())
+        );
+    }
+
+    #[test]
+    fn size_from_str_rejects_negative_number() {
+        assert_eq!(
+            parse_size("-5k").unwrap_err(),
+            CliError::InvalidSizeNumber("-5k".to_string())
+        );
+        assert_eq!(
+            parse_size("-1m").unwrap_err(),
+            CliError::InvalidSizeNumber("-1m".to_string())
+        );
+    }
+
+    #[test]
+    fn min_len_accepts_size_suffix() {
+        let args = parse(vec!["nanoq", "filter", "-l", "5kb"]);
+
+        let actual = match args.unwrap().command {
+            Command::Filter(filter) => filter.min_len,
+            _ => panic!("expected the filter subcommand"),
+        };
+
+        assert_eq!(actual, 5_000);
+    }
+
+    #[test]
+    fn length_and_quality_thresholds_parse_comma_separated_lists() {
+        let args = parse(vec![
+            "nanoq",
+            "stats",
+            "--length-thresholds",
+            "1000,5kb,25000",
+            "--quality-thresholds",
+            "8,12,18",
+        ]);
+
+        let stats = match args.unwrap().command {
+            Command::Stats(stats) => stats,
+            _ => panic!("expected the stats subcommand"),
+        };
+
+        assert_eq!(stats.length_thresholds, vec![1000, 5_000, 25_000]);
+        assert_eq!(stats.quality_thresholds, vec![8, 12, 18]);
+    }
+
+    #[test]
+    fn percentiles_parse_comma_separated_list() {
+        let args = parse(vec!["nanoq", "stats", "--percentiles", "10,50,90"]);
+
+        let stats = match args.unwrap().command {
+            Command::Stats(stats) => stats,
+            _ => panic!("expected the stats subcommand"),
+        };
+
+        assert_eq!(stats.percentiles, vec![10, 50, 90]);
+    }
+
+    #[test]
+    fn nx_percentages_parse_comma_separated_list() {
+        let args = parse(vec!["nanoq", "stats", "--nx", "10,50,90"]);
+
+        let stats = match args.unwrap().command {
+            Command::Stats(stats) => stats,
+            _ => panic!("expected the stats subcommand"),
+        };
+
+        assert_eq!(stats.nx_percentages, vec![10, 50, 90]);
+    }
+
+    #[test]
+    fn min_len_rejects_unknown_suffix() {
+        let args = parse(vec!["nanoq", "filter", "-l", "5xb"]);
+
+        let actual = args.unwrap_err().kind;
+        let expected = clap::ErrorKind::ValueValidation;
+
+        assert_eq!(actual, expected)
+    }
+
+    #[test]
+    fn sample_subcommand_parses_target_by() {
+        let args = parse(vec!["nanoq", "sample", "--target-bases", "500m", "--target-by", "quality"]);
+
+        let sample = match args.unwrap().command {
+            Command::Sample(sample) => sample,
+            _ => panic!("expected the sample subcommand"),
+        };
+
+        assert_eq!(sample.target_bases, Some(500_000_000));
+        assert_eq!(sample.target_by, "quality");
+    }
+
+    #[test]
+    fn sample_subcommand_parses_fraction() {
+        let args = parse(vec!["nanoq", "sample", "--fraction", "0.25"]);
+
+        let sample = match args.unwrap().command {
+            Command::Sample(sample) => sample,
+            _ => panic!("expected the sample subcommand"),
+        };
+
+        assert_eq!(sample.fraction, Some(0.25));
+    }
+
+    #[test]
+    fn genome_size_from_str_suffixes_and_plain() {
+        assert_eq!("500".parse::<GenomeSize>().unwrap(), GenomeSize(500));
+        assert_eq!("20k".parse::<GenomeSize>().unwrap(), GenomeSize(20_000));
+        assert_eq!("5mb".parse::<GenomeSize>().unwrap(), GenomeSize(5_000_000));
+        assert_eq!(
+            "2g".parse::<GenomeSize>().unwrap(),
+            GenomeSize(2_000_000_000)
+        );
+        assert_eq!(
+            "1.5tb".parse::<GenomeSize>().unwrap(),
+            GenomeSize(1_500_000_000_000)
+        );
+    }
+
+    #[test]
+    fn genome_size_from_fasta_sums_sequence_lengths() {
+        let path = std::env::temp_dir().join("nanoq_cli_test_genome.fa");
+        std::fs::write(&path, b">chr1\nACGTACGTAC\n>chr2\nACGT\n").unwrap();
+
+        let size = path.to_str().unwrap().parse::<GenomeSize>().unwrap();
+
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(size, GenomeSize(14));
+    }
+
+    #[test]
+    fn genome_size_from_fai_sums_length_column() {
+        let fasta_path = std::env::temp_dir().join("nanoq_cli_test_genome_fai.fa");
+        let fai_path = std::env::temp_dir().join("nanoq_cli_test_genome_fai.fa.fai");
+        std::fs::write(&fasta_path, b">ignored\nA\n").unwrap();
+        std::fs::write(&fai_path, b"chr1\t1000\t6\t70\t71\nchr2\t2000\t1013\t70\t71\n").unwrap();
+
+        let size = fasta_path.to_str().unwrap().parse::<GenomeSize>().unwrap();
+
+        std::fs::remove_file(&fasta_path).unwrap();
+        std::fs::remove_file(&fai_path).unwrap();
+
+        assert_eq!(size, GenomeSize(3_000));
+    }
+
+    #[test]
+    fn genome_size_rejects_nonexistent_path() {
+        let err = "definitely/does/not/exist.fa"
+            .parse::<GenomeSize>()
+            .unwrap_err();
+
+        assert_eq!(
+            err,
+            CliError::InvalidSizeNumber("definitely/does/not/exist.fa".to_string())
+        );
+    }
 }
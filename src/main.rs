@@ -1,14 +1,174 @@
 use anyhow::{Context, Result};
 use structopt::StructOpt;
 
-use crate::cli::Cli;
-use crate::needlecast::NeedleCast;
-use crate::utils::ReadSet;
+use crate::cli::{Cli, Command, FilterArgs, SampleArgs, StatsArgs};
+use crate::needlecast::{LengthOrQuality, NeedleCast};
+use crate::utils::{P2Estimator, ReadSet, StreamingReadSet};
 
 mod cli;
 mod needlecast;
+mod preprocessor;
 mod utils;
 
+/// A subcommand that knows how to drive `NeedleCast` for its own mode
+///
+/// Each variant of [`Command`](cli::Command) wraps its own argument struct,
+/// which implements this trait so `main` can dispatch without matching on
+/// mode-specific fields itself.
+trait Runner {
+    fn run(&self, needle_cast: &mut NeedleCast) -> Result<()>;
+}
+
+impl Runner for FilterArgs {
+    fn run(&self, needle_cast: &mut NeedleCast) -> Result<()> {
+        match self.fast {
+            true => {
+                needle_cast
+                    .filter_length(
+                        self.min_len as usize,
+                        self.max_len as usize,
+                        self.trim_start,
+                        self.trim_end,
+                    )
+                    .context("unable to process reads")?;
+            }
+            false => {
+                needle_cast
+                    .filter(
+                        self.min_len as usize,
+                        self.max_len as usize,
+                        self.min_qual,
+                        self.max_qual,
+                        self.trim_start,
+                        self.trim_end,
+                        self.min_gc,
+                        self.max_gc,
+                    )
+                    .context("unable to process reads")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Runner for SampleArgs {
+    fn run(&self, needle_cast: &mut NeedleCast) -> Result<()> {
+        match (
+            self.sample_reads,
+            self.coverage,
+            self.sample_bases,
+            self.target_bases,
+            self.fraction,
+        ) {
+            (Some(n), _, _, _, _) => {
+                needle_cast
+                    .subsample_reads(n, self.seed)
+                    .context("unable to subsample reads")?;
+            }
+            (None, Some(coverage), _, _, _) => {
+                let genome_size = self
+                    .genome_size
+                    .context("--coverage requires --genome-size")?;
+                needle_cast
+                    .subsample_coverage(genome_size, coverage, self.seed)
+                    .context("unable to subsample reads")?;
+            }
+            (None, None, Some(sample_bases), _, _) => {
+                needle_cast
+                    .subsample_bases(sample_bases, self.seed)
+                    .context("unable to subsample reads")?;
+            }
+            (None, None, None, Some(target_bases), _) => {
+                let by = match self.target_by.to_lowercase().as_str() {
+                    "quality" => LengthOrQuality::Quality,
+                    _ => LengthOrQuality::Length,
+                };
+                needle_cast
+                    .filter_target(target_bases, by)
+                    .context("unable to select target reads")?;
+            }
+            (None, None, None, None, Some(fraction)) => {
+                needle_cast
+                    .subsample_fraction(fraction, self.seed)
+                    .context("unable to subsample reads")?;
+            }
+            (None, None, None, None, None) => anyhow::bail!(
+                "sample requires one of --sample-reads, --coverage, --sample-bases, --target-bases or --fraction"
+            ),
+        };
+        Ok(())
+    }
+}
+
+impl Runner for StatsArgs {
+    fn run(&self, needle_cast: &mut NeedleCast) -> Result<()> {
+        let (read_lengths, read_qualities, _read_gc, n_filtered) = needle_cast
+            .filter(0, 0, 0.0, 0.0, 0, 0, 0.0, 0.0)
+            .context("unable to process reads")?;
+
+        if self.p2 {
+            let mut length_estimator = P2Estimator::new(0.5);
+            for &length in &read_lengths {
+                length_estimator.add(length as f64);
+            }
+            let mut quality_estimator = P2Estimator::new(0.5);
+            for &quality in &read_qualities {
+                quality_estimator.add(quality as f64);
+            }
+            println!(
+                "{} {} {:.1}",
+                read_lengths.len(),
+                length_estimator.estimate(),
+                quality_estimator.estimate()
+            );
+            return Ok(());
+        }
+
+        if self.stream {
+            let mut stream_set = StreamingReadSet::new();
+            for (i, &length) in read_lengths.iter().enumerate() {
+                stream_set.add(length as u32, read_qualities.get(i).copied());
+            }
+            println!(
+                "{} {} {} {}",
+                stream_set.reads(),
+                stream_set.bases(),
+                stream_set.n50(),
+                stream_set.median_length()
+            );
+            return Ok(());
+        }
+
+        let read_lengths: Vec<u32> = read_lengths.into_iter().map(|l| l as u32).collect();
+        let mut read_set = ReadSet::new(read_lengths, read_qualities);
+
+        read_set
+            .summary(
+                &self.verbose,
+                self.top,
+                self.header,
+                self.stats,
+                self.json,
+                self.report.clone(),
+                n_filtered as u64,
+                &self.length_thresholds,
+                &self.quality_thresholds,
+                &self.percentiles,
+                &self.nx_percentages,
+                self.genome_size,
+            )
+            .context("unable to get summary")?;
+
+        if let Some(path) = self.read_lengths.clone() {
+            read_set.write_read_lengths(path)?;
+        }
+        if let Some(path) = self.read_qualities.clone() {
+            read_set.write_read_qualities(path)?;
+        }
+        Ok(())
+    }
+}
+
 /// Nanoq application
 ///
 /// Run the application from arguments provided
@@ -18,42 +178,9 @@ fn main() -> Result<()> {
     let cli: Cli = Cli::from_args();
     let mut needle_cast = NeedleCast::new(&cli)?;
 
-    let (read_lengths, read_qualities, n_filtered) = match cli.fast {
-        true => needle_cast
-            .filter_length(cli.min_len, cli.max_len, cli.trim_start, cli.trim_end)
-            .context("unable to process reads")?,
-        false => needle_cast
-            .filter(
-                cli.min_len,
-                cli.max_len,
-                cli.min_qual,
-                cli.max_qual,
-                cli.trim_start,
-                cli.trim_end,
-            )
-            .context("unable to process reads")?,
-    };
-
-    let mut read_set = ReadSet::new(read_lengths, read_qualities);
-
-    let output_data = read_set.get_output_data(cli.top, n_filtered);
-
-    read_set
-        .summary(
-            output_data,
-            &cli.verbose,
-            cli.header,
-            cli.stats,
-            cli.json,
-            cli.report,
-        )
-        .context("unable to get summary")?;
-
-    if let Some(path) = cli.read_lengths {
-        read_set.write_read_lengths(path)?;
-    }
-    if let Some(path) = cli.read_qualities {
-        read_set.write_read_qualities(path)?;
+    match &cli.command {
+        Command::Filter(args) => args.run(&mut needle_cast),
+        Command::Stats(args) => args.run(&mut needle_cast),
+        Command::Sample(args) => args.run(&mut needle_cast),
     }
-    Ok(())
 }
@@ -1,16 +1,31 @@
+use gzp::deflate::Bgzf;
+use gzp::par::compress::{ParCompress, ParCompressBuilder};
+use gzp::Compression as GzpCompression;
 use needletail::errors::ParseError;
-use needletail::parser::{write_fasta, write_fastq};
-use needletail::{parse_fastx_file, parse_fastx_stdin, FastxReader};
+use needletail::parser::{write_fasta, write_fastq, LineEnding};
+use needletail::{parse_fastx_reader, FastxReader};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::collections::HashSet;
 use std::fs::File;
-use std::io::{sink, stdout};
-use std::io::{BufWriter, Write};
+use std::io::{sink, stdin, stdout};
+use std::io::{BufRead, BufReader, BufWriter, IoSlice, Read, Write};
+use std::path::{Path, PathBuf};
 use thiserror::Error;
+use zstd::stream::Encoder as ZstdEncoder;
 
-use crate::cli::Cli;
+use crate::cli::{Cli, Command, OutputFormat};
+use crate::preprocessor;
 use crate::utils::CompressionExt;
 
 // Niffler output compression adopted from Michael B. Hall - Rasusa (https://github.com/mbhall88/rasusa)
 
+/// Magic bytes identifying a Zstandard frame
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+/// Leading bytes of a Snappy framing-format stream identifier chunk
+const SNAPPY_FRAME_MAGIC: [u8; 6] = [0xff, 0x06, 0x00, 0x00, b's', b'N'];
+
 /// A collection of custom errors relating to the Needlecast class.
 #[derive(Error, Debug)]
 pub enum NeedlecastError {
@@ -20,6 +35,18 @@ pub enum NeedlecastError {
     /// Indicates error in Niffler compression format
     #[error("Could not get compressed writer")]
     CompressionError(#[from] niffler::Error),
+    /// Indicates that a two-pass mode (coverage, base-budget, or target-yield
+    /// selection) was requested on an input that cannot be re-read for the
+    /// second pass (e.g. stdin)
+    #[error("Two-pass subsampling requires a seekable input file, not stdin")]
+    UnseekableInput,
+    /// Indicates an I/O failure opening the input file or constructing a
+    /// Zstd/Snappy reader or writer
+    #[error("Could not open input file or Zstd/Snappy stream")]
+    Io(#[from] std::io::Error),
+    /// Indicates failure spawning or reading from a `--preprocessor` command
+    #[error("Preprocessor command failed")]
+    Preprocessor(#[from] preprocessor::PreprocessorError),
 }
 
 /// NeedleCast object
@@ -30,6 +57,202 @@ pub enum NeedlecastError {
 pub struct NeedleCast {
     reader: Box<dyn FastxReader>,
     writer: Box<dyn Write>,
+    input: Option<PathBuf>,
+    /// Optional niffler-compressed sink for the `--per-read` report
+    report: Option<Box<dyn Write>>,
+}
+
+/// Ranking key used by [`NeedleCast::filter_target`] to select reads
+pub enum LengthOrQuality {
+    Length,
+    Quality,
+}
+
+/// An owned copy of a parsed record
+///
+/// Needletail's `SequenceRecord` borrows from the reader's internal
+/// buffer, so it cannot be held onto across reads. Subsampling needs to
+/// retain a subset of records while continuing to advance the reader, so
+/// candidate records are copied into this owned form before being stored.
+struct OwnedRecord {
+    id: Vec<u8>,
+    seq: Vec<u8>,
+    qual: Option<Vec<u8>>,
+    line_ending: LineEnding,
+}
+
+/// Size (bytes) at which the buffered writer flushes to the underlying sink
+const WRITE_BUFFER_THRESHOLD: usize = 256 * 1024;
+
+/// Buffers encoded FASTX records and flushes them with `write_vectored`
+///
+/// `filter`/`filter_length` previously wrote straight into `self.writer`,
+/// which costs one `write` syscall per record. This becomes syscall-bound
+/// when piping millions of short nanopore reads. Records are instead
+/// accumulated into an in-memory buffer and flushed in bulk via a single
+/// `write_vectored` call once the buffer reaches `WRITE_BUFFER_THRESHOLD`,
+/// falling back to `write_all` for any remainder the underlying writer
+/// did not accept in one vectored call.
+struct BatchedWriter {
+    inner: Box<dyn Write>,
+    buffer: Vec<u8>,
+    threshold: usize,
+}
+
+impl BatchedWriter {
+    fn new(inner: Box<dyn Write>) -> Self {
+        BatchedWriter::with_threshold(inner, WRITE_BUFFER_THRESHOLD)
+    }
+    fn with_threshold(inner: Box<dyn Write>, threshold: usize) -> Self {
+        BatchedWriter {
+            inner,
+            buffer: Vec::with_capacity(threshold),
+            threshold,
+        }
+    }
+    fn flush_buffer(&mut self) -> std::io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let slice = IoSlice::new(&self.buffer);
+        let written = self.inner.write_vectored(std::slice::from_ref(&slice))?;
+        if written < self.buffer.len() {
+            // The underlying writer does not support (or only partially
+            // honoured) the vectored write; fall back to a plain write.
+            self.inner.write_all(&self.buffer[written..])?;
+        }
+        self.buffer.clear();
+        Ok(())
+    }
+}
+
+impl Write for BatchedWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        if self.buffer.len() >= self.threshold {
+            self.flush_buffer()?;
+        }
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.flush_buffer()?;
+        self.inner.flush()
+    }
+}
+
+impl Drop for BatchedWriter {
+    fn drop(&mut self) {
+        let _ = self.flush_buffer();
+    }
+}
+
+/// Open a FASTX file, transparently decompressing it if needed
+///
+/// Compression is auto-detected from the file's magic bytes (not just its
+/// extension) using the same `niffler` crate already used for output, so
+/// `reads.fastq.gz`/`.bz2`/`.xz` can be piped straight through nanoq without
+/// an external decompressor. Zstd and Snappy, which niffler does not cover,
+/// are sniffed the same way before falling back to niffler for everything else.
+fn open_reader(path: &Path) -> Result<Box<dyn FastxReader>, NeedlecastError> {
+    let file = File::open(path)?;
+    let ext_hint = path.extension().and_then(|ext| ext.to_str());
+    open_fastx(BufReader::new(file), ext_hint)
+}
+
+/// Open stdin as a FASTX reader, transparently decompressing it if needed
+fn open_stdin_reader() -> Result<Box<dyn FastxReader>, NeedlecastError> {
+    open_fastx(BufReader::new(stdin()), None)
+}
+
+/// Sniff Zstd/Snappy from magic bytes or `ext_hint`, otherwise defer to niffler
+fn open_fastx<R: BufRead + Send + 'static>(
+    mut buffered: R,
+    ext_hint: Option<&str>,
+) -> Result<Box<dyn FastxReader>, NeedlecastError> {
+    let magic = buffered.fill_buf()?.to_vec();
+
+    if ext_hint == Some("zst") || magic.starts_with(&ZSTD_MAGIC) {
+        let decoder = zstd::stream::Decoder::new(buffered)?;
+        return Ok(parse_fastx_reader(decoder)?);
+    }
+    if ext_hint == Some("sz") || magic.starts_with(&SNAPPY_FRAME_MAGIC) {
+        let decoder = snap::read::FrameDecoder::new(buffered);
+        return Ok(parse_fastx_reader(decoder)?);
+    }
+
+    let (decompressed, _format) =
+        niffler::send::get_reader(Box::new(buffered) as Box<dyn Read + Send>)?;
+    Ok(parse_fastx_reader(decompressed)?)
+}
+
+/// Build a multithreaded block-gzip (BGZF) writer around `inner`
+///
+/// Splits the stream into independently-deflated blocks compressed across a
+/// thread pool, producing standard `bgzip`/tabix-indexable `.gz` output.
+/// `threads == 0` uses all available logical CPUs.
+fn bgzf_writer<W: Write + Send + 'static>(
+    inner: W,
+    threads: usize,
+    level: niffler::Level,
+) -> Box<dyn Write> {
+    let threads = if threads == 0 { num_cpus::get() } else { threads };
+    let writer: ParCompress<Bgzf> = ParCompressBuilder::new()
+        .num_threads(threads)
+        .expect("failed to build BGZF thread pool")
+        .compression_level(gzp_compression_level(level))
+        .from_writer(inner);
+    Box::new(writer)
+}
+
+/// Map niffler's compression level onto `gzp`'s equivalent
+fn gzp_compression_level(level: niffler::Level) -> GzpCompression {
+    let level = match level {
+        niffler::Level::One => 1,
+        niffler::Level::Two => 2,
+        niffler::Level::Three => 3,
+        niffler::Level::Four => 4,
+        niffler::Level::Five => 5,
+        niffler::Level::Six => 6,
+        niffler::Level::Seven => 7,
+        niffler::Level::Eight => 8,
+        niffler::Level::Nine => 9,
+        // niffler::Level also covers bzip2/xz's wider 0-21 range; clamp into gzip's 1-9
+        niffler::Level::Zero => 1,
+        _ => 9,
+    };
+    GzpCompression::new(level)
+}
+
+/// Map niffler's 1-9 compression level onto zstd's compression level scale
+fn zstd_compression_level(level: niffler::Level) -> i32 {
+    match level {
+        niffler::Level::One => 1,
+        niffler::Level::Two => 2,
+        niffler::Level::Three => 3,
+        niffler::Level::Four => 4,
+        niffler::Level::Five => 5,
+        niffler::Level::Six => 6,
+        niffler::Level::Seven => 7,
+        niffler::Level::Eight => 8,
+        niffler::Level::Nine => 9,
+        // niffler::Level also covers bzip2/xz's wider 0-21 range; clamp into 1-9
+        niffler::Level::Zero => 1,
+        _ => 9,
+    }
+}
+
+impl OwnedRecord {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<(), ParseError> {
+        match &self.qual {
+            Some(qual) => write_fastq(&self.id, &self.seq, Some(qual), writer, self.line_ending),
+            None => write_fasta(&self.id, &self.seq, writer, self.line_ending),
+        }
+    }
+    fn mean_quality(&self) -> Option<f32> {
+        self.qual
+            .as_ref()
+            .map(|qual| -10f32 * mean_error_probability(qual).log(10.0))
+    }
 }
 
 impl NeedleCast {
@@ -49,34 +272,97 @@ impl NeedleCast {
     #[cfg(not(tarpaulin_include))]
     pub fn new(cli: &Cli) -> Result<Self, NeedlecastError> {
         let reader = match &cli.input {
-            Some(file) => parse_fastx_file(file)?,
-            None => parse_fastx_stdin()?,
+            Some(file) if cli.preprocessor.is_some() || preprocessor::has_default(file) => {
+                let preprocessed =
+                    preprocessor::open_preprocessor(file, cli.preprocessor.as_deref())?;
+                parse_fastx_reader(BufReader::new(preprocessed))?
+            }
+            Some(file) => open_reader(file)?,
+            None => open_stdin_reader()?,
         };
+        // The `stats` subcommand only reports on reads, it does not emit a
+        // filtered/sampled FASTX stream, so its output defaults to a sink
+        // unless the caller explicitly asked for `--output`.
+        let is_stats = matches!(&cli.command, Command::Stats(_));
+
         let writer = match &cli.output {
             None => {
-                if cli.stats {
+                if is_stats {
                     Box::new(sink())
                 } else {
                     match cli.output_type {
                         None => Box::new(stdout()),
-                        Some(fmt) => {
+                        Some(OutputFormat::Bgzf) if cli.threads != 1 => {
+                            bgzf_writer(stdout(), cli.threads, cli.compress_level)
+                        }
+                        Some(OutputFormat::Bgzf) => niffler::basic::get_writer(
+                            Box::new(stdout()),
+                            niffler::Format::Gzip,
+                            cli.compress_level,
+                        )?,
+                        Some(OutputFormat::Niffler(fmt)) => {
                             niffler::basic::get_writer(Box::new(stdout()), fmt, cli.compress_level)?
                         }
+                        Some(OutputFormat::Zstd) => {
+                            let level = zstd_compression_level(cli.compress_level);
+                            Box::new(ZstdEncoder::new(stdout(), level)?.auto_finish())
+                        }
+                        Some(OutputFormat::Snappy) => {
+                            Box::new(snap::write::FrameEncoder::new(stdout()))
+                        }
                     }
                 }
             }
             Some(output) => {
                 let file = File::create(output).expect("failed to create output file");
-                let file_handle = Box::new(BufWriter::new(file));
+                let fmt = cli
+                    .output_type
+                    .unwrap_or_else(|| OutputFormat::from_path(output));
 
-                let fmt = match cli.output_type {
-                    None => niffler::Format::from_path(&output),
-                    Some(f) => f,
-                };
-                niffler::get_writer(file_handle, fmt, cli.compress_level)?
+                match fmt {
+                    OutputFormat::Bgzf if cli.threads != 1 => {
+                        bgzf_writer(file, cli.threads, cli.compress_level)
+                    }
+                    OutputFormat::Bgzf => {
+                        let file_handle = Box::new(BufWriter::new(file));
+                        niffler::get_writer(file_handle, niffler::Format::Gzip, cli.compress_level)?
+                    }
+                    OutputFormat::Niffler(f) => {
+                        let file_handle = Box::new(BufWriter::new(file));
+                        niffler::get_writer(file_handle, f, cli.compress_level)?
+                    }
+                    OutputFormat::Zstd => {
+                        let level = zstd_compression_level(cli.compress_level);
+                        let file_handle = BufWriter::new(file);
+                        Box::new(ZstdEncoder::new(file_handle, level)?.auto_finish())
+                    }
+                    OutputFormat::Snappy => {
+                        Box::new(snap::write::FrameEncoder::new(BufWriter::new(file)))
+                    }
+                }
+            }
+        };
+        // Only the `filter` subcommand exposes `--per-read`
+        let per_read = match &cli.command {
+            Command::Filter(args) => args.per_read.as_ref(),
+            _ => None,
+        };
+        let report = match per_read {
+            None => None,
+            Some(path) => {
+                let file = File::create(path).expect("failed to create per-read report file");
+                let file_handle = Box::new(BufWriter::new(file));
+                let fmt = niffler::Format::from_path(path);
+                Some(niffler::get_writer(file_handle, fmt, cli.compress_level)?)
             }
         };
-        Ok(NeedleCast { reader, writer })
+
+        Ok(NeedleCast {
+            reader,
+            writer: Box::new(BatchedWriter::new(writer)),
+            input: cli.input.clone(),
+            report,
+        })
     }
     /// Filter reads and store lengths and qualities
     ///
@@ -95,13 +381,14 @@ impl NeedleCast {
     /// ```compile
     /// use structopt::StructOpt;
     ///
-    /// let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test.fq", "-o", "/dev/null"]);
+    /// let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test.fq", "-o", "/dev/null", "filter"]);
     /// let mut caster = NeedleCast::new(&cli);
-    /// let (read_lengths, read_quals) = caster.filter(0, 0, 0.0).unwrap();
+    /// let (read_lengths, read_quals, read_gc, _) = caster.filter(0, 0, 0.0, 0.0, 0, 0, 0.0, 0.0).unwrap();
     ///
     /// assert_eq!(read_lengths, vec![4]);
     /// assert_eq!(read_quals, vec![40.0]);
     /// ```
+    #[allow(clippy::too_many_arguments)]
     pub fn filter(
         &mut self,
         min_length: usize,
@@ -110,9 +397,12 @@ impl NeedleCast {
         max_quality: f32,
         head_trim: usize,
         tail_trim: usize,
-    ) -> Result<(Vec<usize>, Vec<f32>, usize), ParseError> {
+        min_gc: f32,
+        max_gc: f32,
+    ) -> Result<(Vec<usize>, Vec<f32>, Vec<f32>, usize), ParseError> {
         let mut read_lengths: Vec<usize> = vec![];
         let mut read_qualities: Vec<f32> = vec![];
+        let mut read_gc: Vec<f32> = vec![];
 
         let max_length: usize = if max_length == 0 {
             usize::MAX
@@ -124,6 +414,7 @@ impl NeedleCast {
         let trim_seq = total_trim > 0;
 
         let max_quality = if max_quality == 0. { 100. } else { max_quality };
+        let max_gc = if max_gc == 0. { 1. } else { max_gc };
 
         let mut filtered: usize = 0;
         while let Some(record) = self.reader.next() {
@@ -145,14 +436,28 @@ impl NeedleCast {
             if let Some(qual) = rec.qual() {
                 let mean_error_prob = mean_error_probability(qual);
                 let mean_quality: f32 = -10f32 * mean_error_prob.log(10.0);
+                let gc = gc_fraction(&rec.seq());
                 // FASTQ
                 if seqlen >= min_length
                     && seqlen <= max_length
                     && mean_quality >= min_quality
                     && mean_quality <= max_quality
+                    && gc >= min_gc
+                    && gc <= max_gc
                 {
                     read_lengths.push(seqlen);
                     read_qualities.push(mean_quality);
+                    read_gc.push(gc);
+                    if let Some(report) = &mut self.report {
+                        writeln!(
+                            report,
+                            "{}\t{}\t{:.2}",
+                            String::from_utf8_lossy(rec.id()),
+                            seqlen,
+                            mean_quality
+                        )
+                        .expect("failed to write per-read report");
+                    }
                     match trim_seq {
                         true => write_fastq(
                             rec.id(),
@@ -171,8 +476,14 @@ impl NeedleCast {
                 }
             } else {
                 // FASTA
-                if seqlen >= min_length && seqlen <= max_length {
+                let gc = gc_fraction(&rec.seq());
+                if seqlen >= min_length && seqlen <= max_length && gc >= min_gc && gc <= max_gc {
                     read_lengths.push(seqlen);
+                    read_gc.push(gc);
+                    if let Some(report) = &mut self.report {
+                        writeln!(report, "{}\t{}\t", String::from_utf8_lossy(rec.id()), seqlen)
+                            .expect("failed to write per-read report");
+                    }
                     rec.write(&mut self.writer, None)
                         .expect("failed to write fasta record");
                 } else {
@@ -180,7 +491,7 @@ impl NeedleCast {
                 }
             }
         }
-        Ok((read_lengths, read_qualities, filtered))
+        Ok((read_lengths, read_qualities, read_gc, filtered))
     }
     /// Filter reads and store lengths and qualities
     /// without considering quality scores
@@ -274,6 +585,332 @@ impl NeedleCast {
         }
         Ok((read_lengths, read_qualities, filtered))
     }
+    /// Subsample to a fixed number of reads using reservoir sampling
+    ///
+    /// Single-pass Algorithm R: the first `n` reads are kept outright, and
+    /// for every subsequent read at 0-based index `i` a slot `j` is drawn
+    /// uniformly from `0..=i`; if `j < n` the read at that slot is replaced.
+    /// The read at index `i` enters the reservoir with probability
+    /// `n / (i + 1)`, so after `N` reads every read has an equal `n / N`
+    /// chance of being kept. This makes it suitable for streaming input
+    /// (including stdin) since the total read count never needs to be
+    /// known up front.
+    ///
+    /// # Example
+    ///
+    /// ```compile
+    /// let cli = nanoq::cli::Cli::from_iter(&["nanoq", "sample"]);
+    /// let mut caster = nanoq::needlecast::NeedleCast::new(&cli).unwrap();
+    /// caster.subsample_reads(100, 42).unwrap();
+    /// ```
+    pub fn subsample_reads(
+        &mut self,
+        n: usize,
+        seed: u64,
+    ) -> Result<(Vec<usize>, Vec<f32>, usize), ParseError> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut reservoir: Vec<OwnedRecord> = Vec::with_capacity(n);
+
+        let mut i: usize = 0;
+        while let Some(record) = self.reader.next() {
+            let rec = record.expect("failed to parse record");
+            let owned = OwnedRecord {
+                id: rec.id().to_vec(),
+                seq: rec.seq().to_vec(),
+                qual: rec.qual().map(|q| q.to_vec()),
+                line_ending: rec.line_ending(),
+            };
+            if i < n {
+                reservoir.push(owned);
+            } else {
+                let j = rng.gen_range(0..=i);
+                if j < n {
+                    reservoir[j] = owned;
+                }
+            }
+            i += 1;
+        }
+
+        let filtered = i.saturating_sub(reservoir.len());
+
+        let mut read_lengths = Vec::with_capacity(reservoir.len());
+        let mut read_qualities = Vec::new();
+        for rec in &reservoir {
+            read_lengths.push(rec.seq.len());
+            if let Some(mean_quality) = rec.mean_quality() {
+                read_qualities.push(mean_quality);
+            }
+            rec.write(&mut self.writer)
+                .expect("failed to write sampled record");
+        }
+
+        Ok((read_lengths, read_qualities, filtered))
+    }
+    /// Subsample to a target coverage of a genome using a two-pass scan
+    ///
+    /// A single streaming pass cannot know the total number of bases in
+    /// advance, so this scans the input twice: the first pass sums read
+    /// lengths to get the total `T`, and computes a keep-probability
+    /// `p = min(1.0, (genome_size * coverage) / T)`; the second pass
+    /// re-opens the input and emits each read independently with
+    /// probability `p`. This requires a seekable input file rather than
+    /// stdin, since the reader has to be rebuilt from scratch between
+    /// passes.
+    ///
+    /// # Example
+    ///
+    /// ```compile
+    /// let cli = nanoq::cli::Cli::from_iter(&["nanoq", "-i", "reads.fq", "sample"]);
+    /// let mut caster = nanoq::needlecast::NeedleCast::new(&cli).unwrap();
+    /// caster.subsample_coverage(5_000_000, 30.0, 42).unwrap();
+    /// ```
+    pub fn subsample_coverage(
+        &mut self,
+        genome_size: u64,
+        coverage: f64,
+        seed: u64,
+    ) -> Result<(Vec<usize>, Vec<f32>, usize), NeedlecastError> {
+        let input = self.input.clone().ok_or(NeedlecastError::UnseekableInput)?;
+
+        let mut total_bases: u64 = 0;
+        let mut first_pass = open_reader(&input)?;
+        while let Some(record) = first_pass.next() {
+            let rec = record.expect("failed to parse record");
+            total_bases += rec.num_bases() as u64;
+        }
+
+        let target = (genome_size as f64 * coverage) as u64;
+        let p = if total_bases == 0 {
+            0.0
+        } else {
+            (target as f64 / total_bases as f64).min(1.0)
+        };
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        self.reader = open_reader(&input)?;
+
+        let mut read_lengths = Vec::new();
+        let mut read_qualities = Vec::new();
+        let mut filtered: usize = 0;
+        while let Some(record) = self.reader.next() {
+            let rec = record.expect("failed to parse record");
+            if rng.gen::<f64>() < p {
+                read_lengths.push(rec.num_bases());
+                if let Some(qual) = rec.qual() {
+                    let mean_quality = -10f32 * mean_error_probability(qual).log(10.0);
+                    read_qualities.push(mean_quality);
+                }
+                rec.write(&mut self.writer, None)
+                    .expect("failed to write sampled record");
+            } else {
+                filtered += 1;
+            }
+        }
+
+        Ok((read_lengths, read_qualities, filtered))
+    }
+    /// Subsample by independently keeping each read with probability `fraction`
+    ///
+    /// Unlike `subsample_coverage`, which derives its keep-probability from
+    /// a two-pass scan of the input, this accepts the probability directly
+    /// from the caller, so a single streaming pass suffices and it works on
+    /// unseekable input such as stdin.
+    ///
+    /// # Example
+    ///
+    /// ```compile
+    /// let cli = nanoq::cli::Cli::from_iter(&["nanoq", "-i", "reads.fq", "sample"]);
+    /// let mut caster = nanoq::needlecast::NeedleCast::new(&cli).unwrap();
+    /// caster.subsample_fraction(0.1, 42).unwrap();
+    /// ```
+    pub fn subsample_fraction(
+        &mut self,
+        fraction: f64,
+        seed: u64,
+    ) -> Result<(Vec<usize>, Vec<f32>, usize), ParseError> {
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        let mut read_lengths = Vec::new();
+        let mut read_qualities = Vec::new();
+        let mut filtered: usize = 0;
+        while let Some(record) = self.reader.next() {
+            let rec = record.expect("failed to parse record");
+            if rng.gen::<f64>() < fraction {
+                read_lengths.push(rec.num_bases());
+                if let Some(qual) = rec.qual() {
+                    let mean_quality = -10f32 * mean_error_probability(qual).log(10.0);
+                    read_qualities.push(mean_quality);
+                }
+                rec.write(&mut self.writer, None)
+                    .expect("failed to write sampled record");
+            } else {
+                filtered += 1;
+            }
+        }
+
+        Ok((read_lengths, read_qualities, filtered))
+    }
+    /// Subsample to an exact base budget using a shuffled-permutation scan
+    ///
+    /// Unlike `subsample_coverage`, which accepts each read independently
+    /// with a fixed probability, this targets a base budget exactly (up to
+    /// the length of the read that crosses it): the first pass records
+    /// every read's length and the total base count; a random permutation
+    /// of read indices is then drawn with a seeded RNG and walked in order,
+    /// greedily keeping reads until the running sum of lengths reaches
+    /// `target_bases`. The second pass re-opens the input and emits only
+    /// the kept reads, in their original file order. If the input has no
+    /// more bases than the target, every read is kept and a warning is
+    /// printed to stderr.
+    ///
+    /// # Example
+    ///
+    /// ```compile
+    /// let cli = nanoq::cli::Cli::from_iter(&["nanoq", "-i", "reads.fq", "sample"]);
+    /// let mut caster = nanoq::needlecast::NeedleCast::new(&cli).unwrap();
+    /// caster.subsample_bases(500_000_000, 42).unwrap();
+    /// ```
+    pub fn subsample_bases(
+        &mut self,
+        target_bases: u64,
+        seed: u64,
+    ) -> Result<(Vec<usize>, Vec<f32>, usize), NeedlecastError> {
+        let input = self.input.clone().ok_or(NeedlecastError::UnseekableInput)?;
+
+        let mut lengths: Vec<usize> = Vec::new();
+        let mut total_bases: u64 = 0;
+        let mut first_pass = open_reader(&input)?;
+        while let Some(record) = first_pass.next() {
+            let rec = record.expect("failed to parse record");
+            let len = rec.num_bases();
+            lengths.push(len);
+            total_bases += len as u64;
+        }
+
+        let mut keep: Vec<bool> = vec![false; lengths.len()];
+        if total_bases <= target_bases {
+            keep.iter_mut().for_each(|k| *k = true);
+            eprintln!(
+                "warning: total bases ({}) do not exceed the target ({}), keeping all reads",
+                total_bases, target_bases
+            );
+        } else {
+            let mut order: Vec<usize> = (0..lengths.len()).collect();
+            let mut rng = StdRng::seed_from_u64(seed);
+            order.shuffle(&mut rng);
+
+            let mut cum_bases: u64 = 0;
+            for idx in order {
+                if cum_bases >= target_bases {
+                    break;
+                }
+                keep[idx] = true;
+                cum_bases += lengths[idx] as u64;
+            }
+        }
+
+        self.reader = open_reader(&input)?;
+
+        let mut read_lengths = Vec::new();
+        let mut read_qualities = Vec::new();
+        let mut filtered: usize = 0;
+        let mut index = 0usize;
+        while let Some(record) = self.reader.next() {
+            let rec = record.expect("failed to parse record");
+            if keep[index] {
+                read_lengths.push(rec.num_bases());
+                if let Some(qual) = rec.qual() {
+                    let mean_quality = -10f32 * mean_error_probability(qual).log(10.0);
+                    read_qualities.push(mean_quality);
+                }
+                rec.write(&mut self.writer, None)
+                    .expect("failed to write sampled record");
+            } else {
+                filtered += 1;
+            }
+            index += 1;
+        }
+
+        Ok((read_lengths, read_qualities, filtered))
+    }
+    /// Select the subset of reads maximising retained bases up to a target yield
+    ///
+    /// Picking the best reads up to a requested output yield (e.g. 100x of
+    /// a 5 Mb genome) requires a global ranking, so this can't be done in
+    /// a single streaming pass: the first pass collects `(index, length,
+    /// mean_quality)` for every read, the candidates are sorted descending
+    /// by the requested key, and reads are accepted greedily until
+    /// `target_bases` is reached. A second pass re-streams the input and
+    /// emits only the accepted indices, in their original file order.
+    ///
+    /// # Example
+    ///
+    /// ```compile
+    /// let cli = nanoq::cli::Cli::from_iter(&["nanoq", "-i", "reads.fq", "sample"]);
+    /// let mut caster = nanoq::needlecast::NeedleCast::new(&cli).unwrap();
+    /// caster.filter_target(500_000_000, LengthOrQuality::Length).unwrap();
+    /// ```
+    pub fn filter_target(
+        &mut self,
+        target_bases: u64,
+        by: LengthOrQuality,
+    ) -> Result<(Vec<usize>, Vec<f32>, usize), NeedlecastError> {
+        let input = self.input.clone().ok_or(NeedlecastError::UnseekableInput)?;
+
+        let mut candidates: Vec<(usize, usize, f32)> = Vec::new();
+        let mut first_pass = open_reader(&input)?;
+        let mut index = 0usize;
+        while let Some(record) = first_pass.next() {
+            let rec = record.expect("failed to parse record");
+            let length = rec.num_bases();
+            let mean_quality = rec
+                .qual()
+                .map(|qual| -10f32 * mean_error_probability(qual).log(10.0))
+                .unwrap_or(0.0);
+            candidates.push((index, length, mean_quality));
+            index += 1;
+        }
+
+        match by {
+            LengthOrQuality::Length => candidates.sort_unstable_by(|a, b| b.1.cmp(&a.1)),
+            LengthOrQuality::Quality => {
+                candidates.sort_by(|a, b| b.2.partial_cmp(&a.2).unwrap())
+            }
+        }
+
+        let mut selected: HashSet<usize> = HashSet::new();
+        let mut cum_bases: u64 = 0;
+        for (idx, length, _) in &candidates {
+            if cum_bases >= target_bases {
+                break;
+            }
+            selected.insert(*idx);
+            cum_bases += *length as u64;
+        }
+
+        self.reader = open_reader(&input)?;
+
+        let mut read_lengths = Vec::new();
+        let mut read_qualities = Vec::new();
+        let mut filtered: usize = 0;
+        let mut index = 0usize;
+        while let Some(record) = self.reader.next() {
+            let rec = record.expect("failed to parse record");
+            if selected.contains(&index) {
+                read_lengths.push(rec.num_bases());
+                if let Some(qual) = rec.qual() {
+                    read_qualities.push(-10f32 * mean_error_probability(qual).log(10.0));
+                }
+                rec.write(&mut self.writer, None)
+                    .expect("failed to write record");
+            } else {
+                filtered += 1;
+            }
+            index += 1;
+        }
+
+        Ok((read_lengths, read_qualities, filtered))
+    }
 }
 
 /// Utility function to compute mean error probability from quality bytes
@@ -310,6 +947,21 @@ fn mean_error_probability(quality_bytes: &[u8]) -> f32 {
     sum / quality_bytes.len() as f32 // mean error probability
 }
 
+/// Utility function to compute the fraction of G/C bases in a sequence
+///
+/// Used alongside length and quality to flag contamination- or
+/// adapter-heavy reads in `NeedleCast::filter`.
+fn gc_fraction(seq: &[u8]) -> f32 {
+    if seq.is_empty() {
+        return 0.0;
+    }
+    let gc_count = seq
+        .iter()
+        .filter(|b| matches!(b, b'G' | b'g' | b'C' | b'c'))
+        .count();
+    gc_count as f32 / seq.len() as f32
+}
+
 #[cfg(test)]
 #[cfg(not(tarpaulin_include))] // weirdly includes line from [should_panic] tests
 mod tests {
@@ -337,9 +989,9 @@ mod tests {
     fn needlecast_filter_fq_ok() {
         use structopt::StructOpt;
 
-        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fq", "-o", "/dev/null"]);
+        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fq", "-o", "/dev/null", "filter"]);
         let mut caster = NeedleCast::new(&cli).unwrap();
-        let (read_lengths, read_quals, _) = caster.filter(0, 0, 0.0, 0.0, 0, 0).unwrap();
+        let (read_lengths, read_quals, _, _) = caster.filter(0, 0, 0.0, 0.0, 0, 0, 0.0, 0.0).unwrap();
 
         assert_eq!(read_lengths, vec![4]);
         assert_eq!(read_quals, vec![40.0]);
@@ -349,9 +1001,9 @@ mod tests {
     fn needlecast_filter_max_fq_ok() {
         use structopt::StructOpt;
 
-        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fq", "-o", "/dev/null"]);
+        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fq", "-o", "/dev/null", "filter"]);
         let mut caster = NeedleCast::new(&cli).unwrap();
-        let (read_lengths, read_quals, _) = caster.filter(0, 3, 0.0, 0.0, 0, 0).unwrap();
+        let (read_lengths, read_quals, _, _) = caster.filter(0, 3, 0.0, 0.0, 0, 0, 0.0, 0.0).unwrap();
 
         let expected_length: Vec<usize> = vec![];
         let expected_quality: Vec<f32> = vec![];
@@ -364,7 +1016,30 @@ mod tests {
     fn needlecast_filter_length_fq_ok() {
         use structopt::StructOpt;
 
-        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_len.fq", "-o", "/dev/null"]);
+        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_len.fq", "-o", "/dev/null", "filter"]);
+        let mut caster = NeedleCast::new(&cli).unwrap();
+        let (read_lengths, read_quals, _) = caster.filter_length(0, 0, 0, 0).unwrap();
+
+        let expected_quality: Vec<f32> = vec![];
+
+        assert_eq!(read_lengths, vec![4, 8]);
+        assert_eq!(read_quals, expected_quality);
+    }
+
+    #[test]
+    fn needlecast_preprocessor_cat_reads_fastq() {
+        use structopt::StructOpt;
+
+        let cli = Cli::from_iter(&[
+            "nanoq",
+            "--preprocessor",
+            "cat",
+            "-i",
+            "tests/cases/test_len.fq",
+            "-o",
+            "/dev/null",
+            "filter",
+        ]);
         let mut caster = NeedleCast::new(&cli).unwrap();
         let (read_lengths, read_quals, _) = caster.filter_length(0, 0, 0, 0).unwrap();
 
@@ -378,7 +1053,7 @@ mod tests {
     fn needlecast_filter_length_max_fq_ok() {
         use structopt::StructOpt;
 
-        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_len.fq", "-o", "/dev/null"]);
+        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_len.fq", "-o", "/dev/null", "filter"]);
 
         let mut caster = NeedleCast::new(&cli).unwrap();
         let (read_lengths, read_quals, _) = caster.filter_length(0, 3, 0, 0).unwrap();
@@ -401,7 +1076,7 @@ mod tests {
     fn needlecast_filter_length_min_fq_ok() {
         use structopt::StructOpt;
 
-        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_len.fq", "-o", "/dev/null"]);
+        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_len.fq", "-o", "/dev/null", "filter"]);
 
         let mut caster = NeedleCast::new(&cli).unwrap();
         let (read_lengths, read_quals, _) = caster.filter_length(5, 0, 0, 0).unwrap();
@@ -423,9 +1098,9 @@ mod tests {
     fn needlecast_filter_fa_ok() {
         use structopt::StructOpt;
 
-        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fa", "-o", "/dev/null"]);
+        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fa", "-o", "/dev/null", "filter"]);
         let mut caster = NeedleCast::new(&cli).unwrap();
-        let (read_lengths, read_quals, _) = caster.filter(0, 0, 0.0, 0.0, 0, 0).unwrap();
+        let (read_lengths, read_quals, _, _) = caster.filter(0, 0, 0.0, 0.0, 0, 0, 0.0, 0.0).unwrap();
 
         let expected_quality: Vec<f32> = vec![];
 
@@ -437,7 +1112,7 @@ mod tests {
     fn needlecast_filter_length_fa_ok() {
         use structopt::StructOpt;
 
-        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fa", "-o", "/dev/null"]);
+        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fa", "-o", "/dev/null", "filter"]);
         let mut caster = NeedleCast::new(&cli).unwrap();
         let (read_lengths, read_quals, _) = caster.filter_length(0, 0, 0, 0).unwrap();
 
@@ -447,7 +1122,7 @@ mod tests {
         assert_eq!(read_quals, expected_quality);
 
         let mut caster = NeedleCast::new(&cli).unwrap();
-        let (read_lengths, read_quals, _) = caster.filter(5, 0, 0.0, 0.0, 0, 0).unwrap();
+        let (read_lengths, read_quals, _, _) = caster.filter(5, 0, 0.0, 0.0, 0, 0, 0.0, 0.0).unwrap();
 
         let expected_length: Vec<usize> = vec![];
         let expected_quality: Vec<f32> = vec![];
@@ -460,7 +1135,7 @@ mod tests {
     fn needlecast_filter_length_trim_bigger_read_length_ok() {
         use structopt::StructOpt;
 
-        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fa", "-o", "/dev/null"]);
+        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fa", "-o", "/dev/null", "filter"]);
         let mut caster = NeedleCast::new(&cli).unwrap();
         let (read_lengths, read_quals, _) = caster.filter_length(0, 0, 5, 0).unwrap();
 
@@ -475,7 +1150,7 @@ mod tests {
     fn needlecast_filter_length_head_trim_fa_ok() {
         use structopt::StructOpt;
 
-        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fa", "-o", "/dev/null"]);
+        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fa", "-o", "/dev/null", "filter"]);
         let mut caster = NeedleCast::new(&cli).unwrap();
         let (read_lengths, read_quals, _) = caster.filter_length(0, 0, 2, 0).unwrap();
 
@@ -489,7 +1164,7 @@ mod tests {
     fn needlecast_filter_length_tail_trim_fa_ok() {
         use structopt::StructOpt;
 
-        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fa", "-o", "/dev/null"]);
+        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fa", "-o", "/dev/null", "filter"]);
         let mut caster = NeedleCast::new(&cli).unwrap();
         let (read_lengths, read_quals, _) = caster.filter_length(0, 0, 0, 2).unwrap();
 
@@ -503,7 +1178,7 @@ mod tests {
     fn needlecast_filter_length_head_tail_trim_fa_ok() {
         use structopt::StructOpt;
 
-        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fa", "-o", "/dev/null"]);
+        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fa", "-o", "/dev/null", "filter"]);
         let mut caster = NeedleCast::new(&cli).unwrap();
         let (read_lengths, read_quals, _) = caster.filter_length(0, 0, 1, 1).unwrap();
 
@@ -517,7 +1192,7 @@ mod tests {
     fn needlecast_filter_length_head_trim_fq_ok() {
         use structopt::StructOpt;
 
-        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fq", "-o", "/dev/null"]);
+        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fq", "-o", "/dev/null", "filter"]);
         let mut caster = NeedleCast::new(&cli).unwrap();
         let (read_lengths, read_quals, _) = caster.filter_length(0, 0, 2, 0).unwrap();
 
@@ -531,7 +1206,7 @@ mod tests {
     fn needlecast_filter_length_tail_trim_fq_ok() {
         use structopt::StructOpt;
 
-        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fq", "-o", "/dev/null"]);
+        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fq", "-o", "/dev/null", "filter"]);
         let mut caster = NeedleCast::new(&cli).unwrap();
         let (read_lengths, read_quals, _) = caster.filter_length(0, 0, 0, 2).unwrap();
 
@@ -545,7 +1220,7 @@ mod tests {
     fn needlecast_filter_length_head_tail_trim_fq_ok() {
         use structopt::StructOpt;
 
-        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fq", "-o", "/dev/null"]);
+        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fq", "-o", "/dev/null", "filter"]);
         let mut caster = NeedleCast::new(&cli).unwrap();
         let (read_lengths, read_quals, _) = caster.filter_length(0, 0, 1, 1).unwrap();
 
@@ -559,9 +1234,9 @@ mod tests {
     fn needlecast_filter_trim_bigger_read_length_ok() {
         use structopt::StructOpt;
 
-        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fa", "-o", "/dev/null"]);
+        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fa", "-o", "/dev/null", "filter"]);
         let mut caster = NeedleCast::new(&cli).unwrap();
-        let (read_lengths, read_quals, _) = caster.filter(0, 0, 0.0, 0.0, 5, 0).unwrap();
+        let (read_lengths, read_quals, _, _) = caster.filter(0, 0, 0.0, 0.0, 5, 0, 0.0, 0.0).unwrap();
 
         let expected_quality: Vec<f32> = vec![];
         let expected_lengths: Vec<usize> = vec![];
@@ -574,9 +1249,9 @@ mod tests {
     fn needlecast_filter_head_trim_fa_ok() {
         use structopt::StructOpt;
 
-        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fa", "-o", "/dev/null"]);
+        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fa", "-o", "/dev/null", "filter"]);
         let mut caster = NeedleCast::new(&cli).unwrap();
-        let (read_lengths, read_quals, _) = caster.filter(0, 0, 0.0, 0.0, 2, 0).unwrap();
+        let (read_lengths, read_quals, _, _) = caster.filter(0, 0, 0.0, 0.0, 2, 0, 0.0, 0.0).unwrap();
 
         let expected_quality: Vec<f32> = vec![];
 
@@ -588,9 +1263,9 @@ mod tests {
     fn needlecast_filter_tail_trim_fa_ok() {
         use structopt::StructOpt;
 
-        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fa", "-o", "/dev/null"]);
+        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fa", "-o", "/dev/null", "filter"]);
         let mut caster = NeedleCast::new(&cli).unwrap();
-        let (read_lengths, read_quals, _) = caster.filter(0, 0, 0.0, 0.0, 0, 2).unwrap();
+        let (read_lengths, read_quals, _, _) = caster.filter(0, 0, 0.0, 0.0, 0, 2, 0.0, 0.0).unwrap();
 
         let expected_quality: Vec<f32> = vec![];
 
@@ -602,9 +1277,9 @@ mod tests {
     fn needlecast_filter_head_tail_trim_fa_ok() {
         use structopt::StructOpt;
 
-        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fa", "-o", "/dev/null"]);
+        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fa", "-o", "/dev/null", "filter"]);
         let mut caster = NeedleCast::new(&cli).unwrap();
-        let (read_lengths, read_quals, _) = caster.filter(0, 0, 0.0, 0.0, 1, 1).unwrap();
+        let (read_lengths, read_quals, _, _) = caster.filter(0, 0, 0.0, 0.0, 1, 1, 0.0, 0.0).unwrap();
 
         let expected_quality: Vec<f32> = vec![];
 
@@ -616,9 +1291,9 @@ mod tests {
     fn needlecast_filter_head_trim_fq_ok() {
         use structopt::StructOpt;
 
-        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fq", "-o", "/dev/null"]);
+        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fq", "-o", "/dev/null", "filter"]);
         let mut caster = NeedleCast::new(&cli).unwrap();
-        let (read_lengths, read_quals, _) = caster.filter(0, 0, 0.0, 0.0, 2, 0).unwrap();
+        let (read_lengths, read_quals, _, _) = caster.filter(0, 0, 0.0, 0.0, 2, 0, 0.0, 0.0).unwrap();
 
         let expected_quality: Vec<f32> = vec![40.0];
 
@@ -630,9 +1305,9 @@ mod tests {
     fn needlecast_filter_tail_trim_fq_ok() {
         use structopt::StructOpt;
 
-        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fq", "-o", "/dev/null"]);
+        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fq", "-o", "/dev/null", "filter"]);
         let mut caster = NeedleCast::new(&cli).unwrap();
-        let (read_lengths, read_quals, _) = caster.filter(0, 0, 0.0, 0.0, 0, 2).unwrap();
+        let (read_lengths, read_quals, _, _) = caster.filter(0, 0, 0.0, 0.0, 0, 2, 0.0, 0.0).unwrap();
 
         let expected_quality: Vec<f32> = vec![40.0];
 
@@ -644,9 +1319,9 @@ mod tests {
     fn needlecast_filter_head_tail_trim_fq_ok() {
         use structopt::StructOpt;
 
-        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fq", "-o", "/dev/null"]);
+        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fq", "-o", "/dev/null", "filter"]);
         let mut caster = NeedleCast::new(&cli).unwrap();
-        let (read_lengths, read_quals, _) = caster.filter(0, 0, 0.0, 0.0, 1, 1).unwrap();
+        let (read_lengths, read_quals, _, _) = caster.filter(0, 0, 0.0, 0.0, 1, 1, 0.0, 0.0).unwrap();
 
         let expected_quality: Vec<f32> = vec![40.0];
 
@@ -658,7 +1333,7 @@ mod tests {
     fn needlecast_filter_length_head_tail_trim_min_len_no_reads_fq_ok() {
         use structopt::StructOpt;
 
-        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fq", "-o", "/dev/null"]);
+        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fq", "-o", "/dev/null", "filter"]);
         let mut caster = NeedleCast::new(&cli).unwrap();
         let (read_lengths, read_quals, _) = caster.filter_length(3, 0, 1, 1).unwrap();
 
@@ -673,7 +1348,7 @@ mod tests {
     fn needlecast_filter_length_head_tail_trim_max_len_no_reads_fq_ok() {
         use structopt::StructOpt;
 
-        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fq", "-o", "/dev/null"]);
+        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fq", "-o", "/dev/null", "filter"]);
         let mut caster = NeedleCast::new(&cli).unwrap();
         let (read_lengths, read_quals, _) = caster.filter_length(0, 1, 1, 1).unwrap();
 
@@ -688,7 +1363,7 @@ mod tests {
     fn needlecast_filter_length_head_tail_trim_min_len_no_reads_fa_ok() {
         use structopt::StructOpt;
 
-        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fa", "-o", "/dev/null"]);
+        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fa", "-o", "/dev/null", "filter"]);
         let mut caster = NeedleCast::new(&cli).unwrap();
         let (read_lengths, read_quals, _) = caster.filter_length(3, 0, 1, 1).unwrap();
 
@@ -703,7 +1378,7 @@ mod tests {
     fn needlecast_filter_length_head_tail_trim_max_len_no_reads_fa_ok() {
         use structopt::StructOpt;
 
-        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fa", "-o", "/dev/null"]);
+        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fa", "-o", "/dev/null", "filter"]);
         let mut caster = NeedleCast::new(&cli).unwrap();
         let (read_lengths, read_quals, _) = caster.filter_length(0, 1, 1, 1).unwrap();
 
@@ -719,9 +1394,9 @@ mod tests {
     fn needlecast_filter_fa_fmt_bad() {
         use structopt::StructOpt;
 
-        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_bad1.fa", "-o", "/dev/null"]);
+        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_bad1.fa", "-o", "/dev/null", "filter"]);
         let mut caster = NeedleCast::new(&cli).unwrap();
-        caster.filter(0, 0, 0.0, 0.0, 0, 0).unwrap();
+        caster.filter(0, 0, 0.0, 0.0, 0, 0, 0.0, 0.0).unwrap();
     }
 
     #[test]
@@ -729,9 +1404,9 @@ mod tests {
     fn needlecast_filter_fq_fmt_bad() {
         use structopt::StructOpt;
 
-        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_bad1.fq", "-o", "/dev/null"]);
+        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_bad1.fq", "-o", "/dev/null", "filter"]);
         let mut caster = NeedleCast::new(&cli).unwrap();
-        caster.filter(0, 0, 0.0, 0.0, 0, 0).unwrap();
+        caster.filter(0, 0, 0.0, 0.0, 0, 0, 0.0, 0.0).unwrap();
     }
 
     #[test]
@@ -739,9 +1414,9 @@ mod tests {
     fn needlecast_filter_fq_sep_bad() {
         use structopt::StructOpt;
 
-        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_bad2.fq", "-o", "/dev/null"]);
+        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_bad2.fq", "-o", "/dev/null", "filter"]);
         let mut caster = NeedleCast::new(&cli).unwrap();
-        caster.filter(0, 0, 0.0, 0.0, 0, 0).unwrap();
+        caster.filter(0, 0, 0.0, 0.0, 0, 0, 0.0, 0.0).unwrap();
     }
 
     #[test]
@@ -749,17 +1424,182 @@ mod tests {
     fn needlecast_filter_length_fq_fmt_bad() {
         use structopt::StructOpt;
 
-        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_bad1.fq", "-o", "/dev/null"]);
+        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_bad1.fq", "-o", "/dev/null", "filter"]);
         let mut caster = NeedleCast::new(&cli).unwrap();
         caster.filter_length(0, 0, 0, 0).unwrap();
     }
 
+    #[test]
+    fn needlecast_filter_per_read_report_ok() {
+        use structopt::StructOpt;
+
+        let cli = Cli::from_iter(&[
+            "nanoq",
+            "-i",
+            "tests/cases/test_ok.fq",
+            "-o",
+            "/dev/null",
+            "filter",
+            "--per-read",
+            "/dev/null",
+        ]);
+        let mut caster = NeedleCast::new(&cli).unwrap();
+        let (read_lengths, read_quals, _, _) = caster.filter(0, 0, 0.0, 0.0, 0, 0, 0.0, 0.0).unwrap();
+
+        assert_eq!(read_lengths, vec![4]);
+        assert_eq!(read_quals, vec![40.0]);
+    }
+
+    #[test]
+    fn batched_writer_flushes_at_threshold() {
+        let sink: Vec<u8> = Vec::new();
+        let mut writer = BatchedWriter::with_threshold(Box::new(sink), 8);
+
+        writer.write_all(b"1234").unwrap();
+        assert!(writer.buffer.len() == 4);
+
+        writer.write_all(b"5678").unwrap();
+        // threshold reached, buffer should have been flushed
+        assert!(writer.buffer.is_empty());
+    }
+
+    #[test]
+    fn gc_fraction_computed_correctly() {
+        use float_eq::float_eq;
+
+        float_eq!(gc_fraction(b"GCGC"), 1.0, abs <= f32::EPSILON);
+        float_eq!(gc_fraction(b"AATT"), 0.0, abs <= f32::EPSILON);
+        float_eq!(gc_fraction(b""), 0.0, abs <= f32::EPSILON);
+    }
+
+    #[test]
+    fn needlecast_filter_min_gc_excludes_reads_below_threshold() {
+        use structopt::StructOpt;
+
+        // test_ok.fq/fa hold a single "ACGT" read: GC fraction 0.5
+        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_ok.fq", "-o", "/dev/null", "filter"]);
+        let mut caster = NeedleCast::new(&cli).unwrap();
+        let (read_lengths, _, read_gc, filtered) =
+            caster.filter(0, 0, 0.0, 0.0, 0, 0, 0.6, 0.0).unwrap();
+
+        let expected_lengths: Vec<usize> = vec![];
+        let expected_gc: Vec<f32> = vec![];
+
+        assert_eq!(read_lengths, expected_lengths);
+        assert_eq!(read_gc, expected_gc);
+        assert_eq!(filtered, 1);
+    }
+
+    #[test]
+    fn needlecast_subsample_bases_reproducible_with_same_seed() {
+        use structopt::StructOpt;
+
+        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_len.fq", "-o", "/dev/null", "filter"]);
+        let mut caster_a = NeedleCast::new(&cli).unwrap();
+        let (lengths_a, _, _) = caster_a.subsample_bases(4, 7).unwrap();
+
+        let mut caster_b = NeedleCast::new(&cli).unwrap();
+        let (lengths_b, _, _) = caster_b.subsample_bases(4, 7).unwrap();
+
+        assert_eq!(lengths_a, lengths_b);
+    }
+
+    #[test]
+    fn needlecast_subsample_bases_keeps_all_when_under_target() {
+        use structopt::StructOpt;
+
+        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_len.fq", "-o", "/dev/null", "filter"]);
+        let mut caster = NeedleCast::new(&cli).unwrap();
+        let (read_lengths, _, filtered) = caster.subsample_bases(1_000_000, 42).unwrap();
+
+        assert_eq!(read_lengths.len(), 2);
+        assert_eq!(filtered, 0);
+    }
+
+    #[test]
+    fn needlecast_filter_target_by_length_ok() {
+        use structopt::StructOpt;
+
+        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_len.fq", "-o", "/dev/null", "filter"]);
+        let mut caster = NeedleCast::new(&cli).unwrap();
+        let (read_lengths, _, filtered) = caster
+            .filter_target(4, LengthOrQuality::Length)
+            .unwrap();
+
+        assert_eq!(read_lengths, vec![8]);
+        assert_eq!(filtered, 1);
+    }
+
+    #[test]
+    fn needlecast_subsample_reads_keeps_at_most_n() {
+        use structopt::StructOpt;
+
+        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_len.fq", "-o", "/dev/null", "filter"]);
+        let mut caster = NeedleCast::new(&cli).unwrap();
+        let (read_lengths, _, filtered) = caster.subsample_reads(1, 42).unwrap();
+
+        assert_eq!(read_lengths.len(), 1);
+        assert_eq!(filtered, 1);
+    }
+
+    #[test]
+    fn needlecast_subsample_reads_reproducible_with_same_seed() {
+        use structopt::StructOpt;
+
+        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_len.fq", "-o", "/dev/null", "filter"]);
+        let mut caster_a = NeedleCast::new(&cli).unwrap();
+        let (lengths_a, _, _) = caster_a.subsample_reads(1, 7).unwrap();
+
+        let mut caster_b = NeedleCast::new(&cli).unwrap();
+        let (lengths_b, _, _) = caster_b.subsample_reads(1, 7).unwrap();
+
+        assert_eq!(lengths_a, lengths_b);
+    }
+
+    #[test]
+    fn needlecast_subsample_fraction_reproducible_with_same_seed() {
+        use structopt::StructOpt;
+
+        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_len.fq", "-o", "/dev/null", "filter"]);
+        let mut caster_a = NeedleCast::new(&cli).unwrap();
+        let (lengths_a, _, _) = caster_a.subsample_fraction(0.5, 7).unwrap();
+
+        let mut caster_b = NeedleCast::new(&cli).unwrap();
+        let (lengths_b, _, _) = caster_b.subsample_fraction(0.5, 7).unwrap();
+
+        assert_eq!(lengths_a, lengths_b);
+    }
+
+    #[test]
+    fn needlecast_subsample_fraction_zero_keeps_nothing() {
+        use structopt::StructOpt;
+
+        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_len.fq", "-o", "/dev/null", "filter"]);
+        let mut caster = NeedleCast::new(&cli).unwrap();
+        let (read_lengths, _, filtered) = caster.subsample_fraction(0.0, 42).unwrap();
+
+        assert_eq!(read_lengths, Vec::<usize>::new());
+        assert_eq!(filtered, 2);
+    }
+
+    #[test]
+    fn needlecast_subsample_fraction_one_keeps_everything() {
+        use structopt::StructOpt;
+
+        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_len.fq", "-o", "/dev/null", "filter"]);
+        let mut caster = NeedleCast::new(&cli).unwrap();
+        let (read_lengths, _, filtered) = caster.subsample_fraction(1.0, 42).unwrap();
+
+        assert_eq!(read_lengths.len(), 2);
+        assert_eq!(filtered, 0);
+    }
+
     #[test]
     #[should_panic]
     fn needlecast_filter_length_fq_sep_bad() {
         use structopt::StructOpt;
 
-        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_bad2.fq", "-o", "/dev/null"]);
+        let cli = Cli::from_iter(&["nanoq", "-i", "tests/cases/test_bad2.fq", "-o", "/dev/null", "filter"]);
         let mut caster = NeedleCast::new(&cli).unwrap();
         caster.filter_length(0, 0, 0, 0).unwrap();
     }
@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::Path;
+use std::process::{Child, ChildStdout, Command, Stdio};
+use thiserror::Error;
+
+/// A collection of custom errors relating to external decompression preprocessors.
+#[derive(Error, Debug)]
+pub enum PreprocessorError {
+    /// Indicates that no `--preprocessor` was given and the input's extension
+    /// has no known default decompressor command
+    #[error("{0} has no known decompressor and no --preprocessor was given")]
+    NoPreprocessor(String),
+    /// Indicates that the preprocessor command failed to spawn
+    #[error("Could not spawn preprocessor command '{0}'")]
+    Spawn(String),
+    /// Indicates that the spawned preprocessor's stdout could not be captured
+    #[error("Preprocessor command produced no stdout pipe")]
+    MissingStdout,
+}
+
+/// Default decompressor commands for archive formats niffler cannot read natively
+///
+/// Looked up by file extension (without the leading dot) when no explicit
+/// `--preprocessor` is given.
+fn default_preprocessors() -> HashMap<&'static str, &'static str> {
+    HashMap::from([
+        ("zip", "unzip -p"),
+        ("tar", "tar -xOf"),
+        ("tgz", "tar -xOzf"),
+        ("sra", "fasterq-dump -Z"),
+    ])
+}
+
+/// Whether `path`'s extension has a built-in default preprocessor command
+pub fn has_default(path: &Path) -> bool {
+    let ext = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+    default_preprocessors().contains_key(ext)
+}
+
+/// A FASTX input streamed from an external preprocessor command's stdout
+///
+/// Keeps the child process alive for the duration of reading so its stdout
+/// pipe stays open. On drop the child is killed and reaped, so a consumer
+/// that only reads a prefix of the stream (e.g. `stats` stopping early on a
+/// huge file) doesn't leave a zombie process blocked writing into a full,
+/// unread pipe.
+pub struct PreprocessorReader {
+    child: Child,
+    stdout: ChildStdout,
+}
+
+impl Read for PreprocessorReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.stdout.read(buf)
+    }
+}
+
+impl Drop for PreprocessorReader {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Spawn a preprocessor for `path`, preferring an explicit `--preprocessor`
+/// command over the built-in extension-based defaults
+///
+/// The command is split on whitespace and `path` is appended as its final
+/// argument, e.g. `--preprocessor "zstd -dc"` runs `zstd -dc reads.zst`.
+pub fn open_preprocessor(
+    path: &Path,
+    preprocessor: Option<&str>,
+) -> Result<PreprocessorReader, PreprocessorError> {
+    let command = match preprocessor {
+        Some(cmd) => cmd.to_string(),
+        None => {
+            let ext = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+            default_preprocessors()
+                .get(ext)
+                .map(|cmd| cmd.to_string())
+                .ok_or_else(|| PreprocessorError::NoPreprocessor(path.display().to_string()))?
+        }
+    };
+
+    let mut parts = command.split_whitespace();
+    let program = parts
+        .next()
+        .ok_or_else(|| PreprocessorError::Spawn(command.clone()))?;
+
+    let mut child = Command::new(program)
+        .args(parts)
+        .arg(path)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|_| PreprocessorError::Spawn(command.clone()))?;
+
+    let stdout = child.stdout.take().ok_or(PreprocessorError::MissingStdout)?;
+
+    Ok(PreprocessorReader { child, stdout })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read as _;
+
+    #[test]
+    fn open_preprocessor_runs_explicit_command() {
+        let mut reader =
+            open_preprocessor(Path::new("tests/cases/test_ok.fq"), Some("cat")).unwrap();
+        let mut contents = String::new();
+        reader.read_to_string(&mut contents).unwrap();
+
+        assert!(contents.starts_with('@'));
+    }
+
+    #[test]
+    fn has_default_recognises_known_extensions() {
+        assert!(has_default(Path::new("reads.zip")));
+        assert!(has_default(Path::new("reads.sra")));
+        assert!(!has_default(Path::new("reads.fq")));
+    }
+
+    #[test]
+    fn open_preprocessor_errors_without_default_or_override() {
+        match open_preprocessor(Path::new("reads.fq"), None) {
+            Err(err) => assert!(matches!(err, PreprocessorError::NoPreprocessor(_))),
+            Ok(_) => panic!("expected PreprocessorError::NoPreprocessor"),
+        }
+    }
+}
@@ -1,8 +1,8 @@
 use anyhow::Result;
 use indoc::formatdoc;
-use serde::Serialize;
-use std::cmp::Ordering;
-use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeMap, BinaryHeap};
 use std::ffi::OsStr;
 use std::fs::File;
 use std::io::Write;
@@ -14,6 +14,8 @@ const LENGTH_THRESHOLDS: [u64; 10] = [
     200, 500, 1000, 2000, 5000, 10000, 30000, 50000, 100000, 1000000,
 ];
 const QUALITY_THRESHOLDS: [u64; 8] = [5, 7, 10, 12, 15, 20, 25, 30];
+/// Percentiles (0-100) of read length/quality reported by default
+const DEFAULT_PERCENTILES: [u64; 5] = [10, 25, 75, 90, 99];
 
 /// A collection of custom errors relating to the utility components for this package.
 #[derive(Error, Debug)]
@@ -43,12 +45,25 @@ impl CompressionExt for niffler::compression::Format {
         match path.extension().map(|s| s.to_str()) {
             Some(Some("gz")) => Self::Gzip,
             Some(Some("bz") | Some("bz2")) => Self::Bzip,
-            Some(Some("lzma")) => Self::Lzma,
+            Some(Some("lzma") | Some("xz")) => Self::Lzma,
             _ => Self::No,
         }
     }
 }
 
+/// Attempts to infer the output format from the file extension, extending
+/// niffler's detection with the Zstd/Snappy formats niffler does not cover.
+impl CompressionExt for crate::cli::OutputFormat {
+    fn from_path<S: AsRef<OsStr> + ?Sized>(p: &S) -> Self {
+        let path = Path::new(p);
+        match path.extension().map(|s| s.to_str()) {
+            Some(Some("zst")) => Self::Zstd,
+            Some(Some("sz")) => Self::Snappy,
+            _ => Self::Niffler(niffler::compression::Format::from_path(p)),
+        }
+    }
+}
+
 #[derive(Serialize)]
 /// Output data for JSON
 pub struct OutputData {
@@ -61,13 +76,37 @@ pub struct OutputData {
     median_length: u32,
     mean_quality: f32,
     median_quality: f32,
+    /// Population variance of read lengths
+    length_variance: f64,
+    /// Population standard deviation of read lengths
+    length_stddev: f64,
+    /// Population variance of read qualities, `NaN` if no quality data
+    quality_variance: f64,
+    /// Population standard deviation of read qualities, `NaN` if no quality data
+    quality_stddev: f64,
     length_thresholds: BTreeMap<u64, u64>,
     quality_thresholds: BTreeMap<u64, u64>,
+    /// Read lengths at the requested percentiles, keyed by percentile
+    length_percentiles: BTreeMap<u64, u32>,
+    /// Read qualities at the requested percentiles, keyed by percentile
+    quality_percentiles: BTreeMap<u64, f32>,
     top_lengths: Vec<u32>,
     top_qualities: Vec<f32>,
-    filtered: u64
+    filtered: u64,
+    /// Read length at which cumulative bases reach x% of total, keyed by x (e.g. 50 -> N50)
+    nx: BTreeMap<u64, u64>,
+    /// Number of reads needed to reach x% of total bases, keyed by x (e.g. 50 -> L50)
+    lx: BTreeMap<u64, u64>,
+    /// Area under the Nx curve: sum(l_i^2) / sum(l_i)
+    aun: f64,
+    /// Read length at which x% of a supplied genome size is covered, keyed by x
+    /// (e.g. 50 -> NG50); empty if no `--genome-size` was given
+    ngx: BTreeMap<u64, u64>,
 }
 
+/// Nx/Lx percentages reported by default alongside N50/L50
+const NX_PERCENTAGES: [u64; 3] = [10, 50, 90];
+
 impl OutputData {
     pub fn get_string(
         &self,
@@ -123,12 +162,14 @@ impl OutputData {
                 };
 
                 let output_string = if verbosity > &1 {
-                    self.add_thresholds(
+                    let output_string = self.add_thresholds(
                         output_string,
                         read_qualities,
                         &self.length_thresholds,
                         &self.quality_thresholds,
-                    )?
+                    )?;
+                    let output_string = self.add_contiguity(output_string)?;
+                    self.add_dispersion(output_string, read_qualities)?
                 } else {
                     output_string
                 };
@@ -156,88 +197,94 @@ impl OutputData {
     ) -> Result<String, UtilityError> {
         let n_reads = self.reads;
 
-        let length_thresholds: Vec<u64> = length_thresholds.values().cloned().collect();
-
-        let _length_thresholds = formatdoc! {"
-            Read length thresholds (bp)
-            
-            > 200       {l200:<12}      {lp200:04.1}%
-            > 500       {l500:<12}      {lp500:04.1}%
-            > 1000      {l1000:<12}      {lp1000:04.1}%
-            > 2000      {l2000:<12}      {lp2000:04.1}%
-            > 5000      {l5000:<12}      {lp5000:04.1}%
-            > 10000     {l10000:<12}      {lp10000:04.1}%
-            > 30000     {l30000:<12}      {lp30000:04.1}%
-            > 50000     {l50000:<12}      {lp50000:04.1}%
-            > 100000    {l100000:<12}      {lp100000:04.1}%
-            > 1000000   {l1000000:<12}      {lp1000000:04.1}%
-            ",
-            l200=length_thresholds[0],
-            l500=length_thresholds[1],
-            l1000=length_thresholds[2],
-            l2000=length_thresholds[3],
-            l5000=length_thresholds[4],
-            l10000=length_thresholds[5],
-            l30000=length_thresholds[6],
-            l50000=length_thresholds[7],
-            l100000=length_thresholds[8],
-            l1000000=length_thresholds[9],
-            lp200=get_length_percent(length_thresholds[0], n_reads),
-            lp500=get_length_percent(length_thresholds[1], n_reads),
-            lp1000=get_length_percent(length_thresholds[2], n_reads),
-            lp2000=get_length_percent(length_thresholds[3], n_reads),
-            lp5000=get_length_percent(length_thresholds[4], n_reads),
-            lp10000=get_length_percent(length_thresholds[5], n_reads),
-            lp30000=get_length_percent(length_thresholds[6], n_reads),
-            lp50000=get_length_percent(length_thresholds[7], n_reads),
-            lp100000=get_length_percent(length_thresholds[8], n_reads),
-            lp1000000=get_length_percent(length_thresholds[9], n_reads),
-        };
-
-        output_string.push_str(&_length_thresholds);
+        output_string.push_str("Read length thresholds (bp)\n\n");
+        for (&edge, &count) in length_thresholds.iter() {
+            output_string.push_str(&format!(
+                "> {:<10} {:<12} {:04.1}%\n",
+                edge,
+                count,
+                get_length_percent(count, n_reads)
+            ));
+        }
+        output_string.push('\n');
 
         let output_string = if !read_qualities.is_empty() {
-            let quality_thresholds: Vec<u64> = quality_thresholds.values().cloned().collect();
-
-            let _quality_thresholds = formatdoc! {"\n
-                Read quality thresholds (Q)
-                
-                > 5   {q5:<12}  {qp5:04.1}%
-                > 7   {q7:<12}  {qp7:04.1}%
-                > 10  {q10:<12}  {qp10:04.1}%
-                > 12  {q12:<12}  {qp12:04.1}%
-                > 15  {q15:<12}  {qp15:04.1}%
-                > 20  {q20:<12}  {qp20:04.1}%
-                > 25  {q25:<12}  {qp25:04.1}%
-                > 30  {q30:<12}  {qp30:04.1}%
-                \n",
-                q5=quality_thresholds[0],
-                q7=quality_thresholds[1],
-                q10=quality_thresholds[2],
-                q12=quality_thresholds[3],
-                q15=quality_thresholds[4],
-                q20=quality_thresholds[5],
-                q25=quality_thresholds[6],
-                q30=quality_thresholds[7],
-                qp5=get_quality_percent(quality_thresholds[0], n_reads),
-                qp7=get_quality_percent(quality_thresholds[1], n_reads),
-                qp10=get_quality_percent(quality_thresholds[2], n_reads),
-                qp12=get_quality_percent(quality_thresholds[3], n_reads),
-                qp15=get_quality_percent(quality_thresholds[4], n_reads),
-                qp20=get_quality_percent(quality_thresholds[5], n_reads),
-                qp25=get_quality_percent(quality_thresholds[6], n_reads),
-                qp30=get_quality_percent(quality_thresholds[7], n_reads),
-            };
-            output_string.push_str(&_quality_thresholds);
+            output_string.push_str("\nRead quality thresholds (Q)\n\n");
+            for (&edge, &count) in quality_thresholds.iter() {
+                output_string.push_str(&format!(
+                    "> {:<5} {:<12} {:04.1}%\n",
+                    edge,
+                    count,
+                    get_quality_percent(count, n_reads)
+                ));
+            }
+            output_string.push('\n');
             output_string
         } else {
-            let _quality_thresholds = String::from("\n");
-            output_string.push_str(&_quality_thresholds);
+            output_string.push('\n');
             output_string
         };
 
         Ok(output_string)
     }
+    /// Add Nx/Lx contiguity metrics and auN to the output string
+    ///
+    /// Used internally by the `summary` method.
+    fn add_contiguity(&self, mut output_string: String) -> Result<String, UtilityError> {
+        output_string.push_str("Contiguity metrics\n\n");
+        for (&x, &nx) in self.nx.iter() {
+            let lx = self.lx.get(&x).copied().unwrap_or(0);
+            output_string.push_str(&format!("N{:<3} {:<12} L{:<3} {:<12}\n", x, nx, x, lx));
+        }
+        output_string.push_str(&format!("auN  {:.1}\n\n", self.aun));
+
+        if !self.ngx.is_empty() {
+            output_string.push('\n');
+            for (&x, &ngx) in self.ngx.iter() {
+                output_string.push_str(&format!("NG{:<3} {:<12}\n", x, ngx));
+            }
+            output_string.push('\n');
+        }
+
+        Ok(output_string)
+    }
+    /// Add read length/quality percentiles and dispersion (variance, std. dev.)
+    ///
+    /// Used internally by the `summary` method.
+    fn add_dispersion(
+        &self,
+        mut output_string: String,
+        read_qualities: &[f32],
+    ) -> Result<String, UtilityError> {
+        output_string.push_str("Dispersion\n\n");
+        output_string.push_str(&format!(
+            "Length variance:      {:.1}\nLength std. dev.:     {:.1}\n",
+            self.length_variance, self.length_stddev
+        ));
+        if !read_qualities.is_empty() {
+            output_string.push_str(&format!(
+                "Quality variance:     {:.2}\nQuality std. dev.:    {:.2}\n",
+                self.quality_variance, self.quality_stddev
+            ));
+        }
+        output_string.push('\n');
+
+        output_string.push_str("\nRead length percentiles (bp)\n\n");
+        for (&p, &value) in self.length_percentiles.iter() {
+            output_string.push_str(&format!("P{:<3} {:<12}\n", p, value));
+        }
+        output_string.push('\n');
+
+        if !read_qualities.is_empty() {
+            output_string.push_str("\nRead quality percentiles (Q)\n\n");
+            for (&p, &value) in self.quality_percentiles.iter() {
+                output_string.push_str(&format!("P{:<3} {:<12.1}\n", p, value));
+            }
+            output_string.push('\n');
+        }
+
+        Ok(output_string)
+    }
     /// Print top ranking read lengths and qualities to stderr
     ///
     /// Used internally by the summary method.
@@ -265,6 +312,112 @@ impl OutputData {
     }
 }
 
+/// Schema version of the [`Summary`] JSON document, bumped on breaking changes
+/// to its shape so downstream consumers can detect incompatible summaries.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Read length statistics, nested under [`Summary`]
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct LengthStats {
+    pub longest: u32,
+    pub shortest: u32,
+    pub mean: u32,
+    pub median: u32,
+    pub n50: u64,
+    pub variance: f64,
+    pub stddev: f64,
+    /// Read lengths at requested percentiles, keyed by percentile
+    pub percentiles: BTreeMap<u64, u32>,
+    /// Read counts above each threshold (bp), keyed by threshold
+    pub thresholds: BTreeMap<u64, u64>,
+}
+
+/// Read quality statistics, nested under [`Summary`]
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct QualityStats {
+    pub mean: f32,
+    pub median: f32,
+    pub variance: f64,
+    pub stddev: f64,
+    /// Read qualities at requested percentiles, keyed by percentile
+    pub percentiles: BTreeMap<u64, f32>,
+    /// Read counts above each threshold (Q), keyed by threshold
+    pub thresholds: BTreeMap<u64, u64>,
+}
+
+/// Assembly-style contiguity metrics, nested under [`Summary`]
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct ContiguityStats {
+    /// Read length at which x% of total bases are covered, keyed by x
+    pub nx: BTreeMap<u64, u64>,
+    /// Number of reads needed to reach x% of total bases, keyed by x
+    pub lx: BTreeMap<u64, u64>,
+    /// Read length at which x% of a supplied genome size is covered, keyed by x
+    pub ngx: BTreeMap<u64, u64>,
+    /// Area under the Nx curve: sum(l_i^2) / sum(l_i)
+    pub aun: f64,
+}
+
+/// Versioned, round-trippable summary document
+///
+/// Unlike [`OutputData`], which also carries the human-readable report
+/// rendering logic, `Summary` is a plain serde data contract: it can be
+/// written to and read back from JSON (e.g. to merge pre-computed summaries
+/// without re-reading the original reads) and is covered by a round-trip
+/// test rather than just "serializes without error". Without quality data
+/// (e.g. FASTA input) the quality fields are `NaN`, which serde_json writes
+/// as `null`; such a document does not deserialize back into a `Summary`.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Summary {
+    pub schema_version: u32,
+    pub reads: u64,
+    pub bases: u64,
+    pub filtered: u64,
+    pub length: LengthStats,
+    pub quality: QualityStats,
+    pub contiguity: ContiguityStats,
+    pub top_lengths: Vec<u32>,
+    pub top_qualities: Vec<f32>,
+}
+
+impl From<&OutputData> for Summary {
+    fn from(data: &OutputData) -> Self {
+        Summary {
+            schema_version: SCHEMA_VERSION,
+            reads: data.reads,
+            bases: data.bases,
+            filtered: data.filtered,
+            length: LengthStats {
+                longest: data.longest,
+                shortest: data.shortest,
+                mean: data.mean_length,
+                median: data.median_length,
+                n50: data.n50,
+                variance: data.length_variance,
+                stddev: data.length_stddev,
+                percentiles: data.length_percentiles.clone(),
+                thresholds: data.length_thresholds.clone(),
+            },
+            quality: QualityStats {
+                mean: data.mean_quality,
+                median: data.median_quality,
+                variance: data.quality_variance,
+                stddev: data.quality_stddev,
+                percentiles: data.quality_percentiles.clone(),
+                thresholds: data.quality_thresholds.clone(),
+            },
+            contiguity: ContiguityStats {
+                nx: data.nx.clone(),
+                lx: data.lx.clone(),
+                ngx: data.ngx.clone(),
+                aun: data.aun,
+            },
+            top_lengths: data.top_lengths.clone(),
+            top_qualities: data.top_qualities.clone(),
+        }
+    }
+}
+
 /// ReadSet object
 ///
 /// Read set objects are mutable to allow
@@ -325,13 +478,31 @@ impl ReadSet {
         stats: bool,
         json: bool,
         report: Option<PathBuf>,
-        filtered: u64
+        filtered: u64,
+        length_thresholds: &[u64],
+        quality_thresholds: &[u64],
+        percentiles: &[u64],
+        nx_percentages: &[u64],
+        genome_size: Option<u64>,
     ) -> Result<(), UtilityError> {
         let length_range = self.range_length();
 
-        let (length_thresholds, quality_thresholds) = self.get_thresholds();
+        let (length_thresholds, quality_thresholds) =
+            self.get_thresholds(length_thresholds, quality_thresholds);
+        let (length_percentiles, quality_percentiles) = self.get_percentiles(percentiles);
         let (top_lengths, top_qualities) = self.get_ranking(top);
 
+        let nx_percentages = if nx_percentages.is_empty() {
+            NX_PERCENTAGES.to_vec()
+        } else {
+            nx_percentages.to_vec()
+        };
+        let (nx, lx, aun) = self.nx_lx_aun(&nx_percentages);
+        let ngx = match genome_size {
+            Some(genome_size) => self.ngx_values(&nx_percentages, genome_size),
+            None => BTreeMap::new(),
+        };
+
         let output_data = OutputData {
             reads: self.reads(),
             bases: self.bases(),
@@ -342,18 +513,29 @@ impl ReadSet {
             median_length: self.median_length(),
             mean_quality: self.mean_quality(),
             median_quality: self.median_quality(),
+            length_variance: self.length_variance(),
+            length_stddev: self.length_stddev(),
+            quality_variance: self.quality_variance(),
+            quality_stddev: self.quality_stddev(),
             length_thresholds,
             quality_thresholds,
+            length_percentiles,
+            quality_percentiles,
             top_lengths,
             top_qualities,
-            filtered
+            filtered,
+            nx,
+            lx,
+            aun,
+            ngx,
         };
 
         let output_string = output_data.get_string(verbosity, header, &self.read_qualities)?;
+        let summary = Summary::from(&output_data);
 
         match report {
             Some(file) => match json {
-                true => serde_json::to_writer(File::create(&file)?, &output_data)?,
+                true => serde_json::to_writer(File::create(&file)?, &summary)?,
                 false => {
                     let mut file_handle = File::create(&file)?;
                     write!(file_handle, "{}", &output_string)?;
@@ -363,7 +545,7 @@ impl ReadSet {
                 // If no report file is specified, output the report to
                 // stdout with the --stats flag
                 let output_string = match json {
-                    true => serde_json::to_string_pretty(&output_data)?,
+                    true => serde_json::to_string_pretty(&summary)?,
                     false => output_string,
                 };
                 match stats {
@@ -375,35 +557,72 @@ impl ReadSet {
 
         Ok(())
     }
+    /// Write each read length, one per line, to `path`
+    pub fn write_read_lengths(&self, path: PathBuf) -> Result<(), UtilityError> {
+        let mut file = File::create(&path)?;
+        for length in &self.read_lengths {
+            writeln!(file, "{}", length)?;
+        }
+        Ok(())
+    }
+    /// Write each read's mean quality, one per line, to `path`
+    pub fn write_read_qualities(&self, path: PathBuf) -> Result<(), UtilityError> {
+        let mut file = File::create(&path)?;
+        for quality in &self.read_qualities {
+            writeln!(file, "{:.1}", quality)?;
+        }
+        Ok(())
+    }
     // Get read length and quality thresholds
-    pub fn get_thresholds(&self) -> (BTreeMap<u64, u64>, BTreeMap<u64, u64>) {
-        let mut thresholds = ThresholdCounter::new();
+    //
+    // Falls back to the built-in `LENGTH_THRESHOLDS`/`QUALITY_THRESHOLDS`
+    // progressions when the caller-supplied slice is empty (e.g. the CLI
+    // options were not set).
+    pub fn get_thresholds(
+        &self,
+        length_thresholds: &[u64],
+        quality_thresholds: &[u64],
+    ) -> (BTreeMap<u64, u64>, BTreeMap<u64, u64>) {
+        let length_edges = if length_thresholds.is_empty() {
+            LENGTH_THRESHOLDS.to_vec()
+        } else {
+            length_thresholds.to_vec()
+        };
+        let quality_edges = if quality_thresholds.is_empty() {
+            QUALITY_THRESHOLDS.to_vec()
+        } else {
+            quality_thresholds.to_vec()
+        };
+        let thresholds = ThresholdCounter::with_thresholds(length_edges, quality_edges);
         let length_thresholds = thresholds.length(&self.read_lengths);
         let quality_thresholds = thresholds.quality(&self.read_qualities);
         (length_thresholds, quality_thresholds)
     }
-    // Get the top ranking read lengths and mean read qualities
-    pub fn get_ranking(&mut self, top: usize) -> (Vec<u32>, Vec<f32>) {
-        let max = match (self.reads() as usize) < top {
-            true => self.reads() as usize,
-            false => top,
+    // Get read length and quality percentiles
+    //
+    // Falls back to the built-in `DEFAULT_PERCENTILES` progression when the
+    // caller-supplied slice is empty (e.g. the CLI option was not set).
+    pub fn get_percentiles(
+        &mut self,
+        percentiles: &[u64],
+    ) -> (BTreeMap<u64, u32>, BTreeMap<u64, f32>) {
+        let percentiles = if percentiles.is_empty() {
+            DEFAULT_PERCENTILES.to_vec()
+        } else {
+            percentiles.to_vec()
         };
-        self.read_lengths.sort_unstable();
-        self.read_lengths.reverse();
-
-        let mut top_lengths = Vec::new();
-        for i in 0..max {
-            top_lengths.push(self.read_lengths[i])
-        }
-
-        let mut top_qualities = Vec::new();
-        if !self.read_qualities.is_empty() {
-            self.read_qualities
-                .sort_by(|a, b| b.partial_cmp(a).unwrap());
-            for i in 0..max {
-                top_qualities.push(self.read_qualities[i]);
-            }
-        }
+        let length_percentiles = self.length_percentiles(&percentiles);
+        let quality_percentiles = self.quality_percentiles(&percentiles);
+        (length_percentiles, quality_percentiles)
+    }
+    // Get the top ranking read lengths and mean read qualities
+    //
+    // Selects the `top` largest lengths/qualities with a capacity-`top`
+    // min-heap (O(n log top)) rather than sorting the whole vector, and
+    // leaves the underlying read length/quality vectors untouched.
+    pub fn get_ranking(&self, top: usize) -> (Vec<u32>, Vec<f32>) {
+        let top_lengths = top_k_lengths(&self.read_lengths, top);
+        let top_qualities = top_k_qualities(&self.read_qualities, top);
         (top_lengths, top_qualities)
     }
     /// Get the number of reads
@@ -502,19 +721,189 @@ impl ReadSet {
     /// assert_eq!(actual, expected);
     /// ```
     pub fn n50(&mut self) -> u64 {
+        self.nx(50.0)
+    }
+    /// Get the read length at which x% of total bases are contained in reads
+    /// at least that long
+    ///
+    /// Generalizes `n50` (`nx(50.0)`) to an arbitrary percentage `x` (0-100).
+    ///
+    /// # Example
+    ///
+    /// ```compile
+    /// let actual = read_set.nx(50.0);
+    /// let expected = 1000;
+    /// assert_eq!(actual, expected);
+    /// ```
+    pub fn nx(&mut self, x: f64) -> u64 {
+        self.read_lengths.sort_unstable();
+        self.read_lengths.reverse();
+        let total_bases = self.bases();
+        if total_bases == 0 {
+            return 0;
+        }
+        let stop = (total_bases as f64 * x / 100.0).ceil() as u64;
+        let mut cum_bases: u64 = 0;
+        for &length in self.read_lengths.iter() {
+            cum_bases += length as u64;
+            if cum_bases >= stop {
+                return length as u64;
+            }
+        }
+        0
+    }
+    /// Get the number of (longest) reads needed to reach x% of total bases
+    ///
+    /// # Example
+    ///
+    /// ```compile
+    /// let actual = read_set.lx(50.0);
+    /// let expected = 1;
+    /// assert_eq!(actual, expected);
+    /// ```
+    pub fn lx(&mut self, x: f64) -> u64 {
         self.read_lengths.sort_unstable();
         self.read_lengths.reverse();
-        let _stop = self.bases() / 2;
-        let mut n50: u64 = 0;
-        let mut _cum_sum: u64 = 0;
-        for x in self.read_lengths.iter().map(|&i| i as u64) {
-            _cum_sum += x;
-            if _cum_sum >= _stop {
-                n50 += x;
-                break;
+        let total_bases = self.bases();
+        if total_bases == 0 {
+            return 0;
+        }
+        let stop = (total_bases as f64 * x / 100.0).ceil() as u64;
+        let mut cum_bases: u64 = 0;
+        for (count, &length) in self.read_lengths.iter().enumerate() {
+            cum_bases += length as u64;
+            if cum_bases >= stop {
+                return count as u64 + 1;
             }
         }
-        n50
+        0
+    }
+    /// Get the read length at which x% of a supplied genome size is covered
+    /// by reads at least that long (NGx)
+    ///
+    /// Like `nx`, but measured against an external `genome_size` rather than
+    /// the total bases sequenced, e.g. to report NG50 relative to a
+    /// reference genome rather than the assembly/read set itself.
+    ///
+    /// # Example
+    ///
+    /// ```compile
+    /// let actual = read_set.ngx(50.0, 2000);
+    /// let expected = 1000;
+    /// assert_eq!(actual, expected);
+    /// ```
+    pub fn ngx(&mut self, x: f64, genome_size: u64) -> u64 {
+        self.read_lengths.sort_unstable();
+        self.read_lengths.reverse();
+        if genome_size == 0 {
+            return 0;
+        }
+        let stop = (genome_size as f64 * x / 100.0).ceil() as u64;
+        let mut cum_bases: u64 = 0;
+        for &length in self.read_lengths.iter() {
+            cum_bases += length as u64;
+            if cum_bases >= stop {
+                return length as u64;
+            }
+        }
+        0
+    }
+    /// Get Nx, Lx, and auN contiguity metrics for several percentages in one pass
+    ///
+    /// * `percentages` - the x values (e.g. `[10, 50, 90]`) at which to
+    ///   report the read length (Nx) at which cumulative bases reach x% of
+    ///   the total, and the number of reads needed to reach that point (Lx)
+    ///
+    /// auN, the area under the Nx curve, is `sum(l_i^2) / sum(l_i)` over all
+    /// read lengths and summarises the whole length distribution in one
+    /// number that does not depend on an arbitrary cutoff such as 50%.
+    ///
+    /// # Example
+    ///
+    /// ```compile
+    /// let (nx, lx, aun) = read_set.nx_lx_aun(&[50]);
+    /// let expected_n50 = 1000;
+    /// assert_eq!(nx[&50], expected_n50);
+    /// ```
+    pub fn nx_lx_aun(&mut self, percentages: &[u64]) -> (BTreeMap<u64, u64>, BTreeMap<u64, u64>, f64) {
+        self.read_lengths.sort_unstable();
+        self.read_lengths.reverse();
+
+        let total_bases = self.bases();
+
+        let mut stops: Vec<(u64, u64)> = percentages
+            .iter()
+            .map(|&x| {
+                let stop = (total_bases as f64 * x as f64 / 100.0).ceil() as u64;
+                (x, stop)
+            })
+            .collect();
+        stops.sort_by_key(|&(_, stop)| stop);
+
+        let mut nx: BTreeMap<u64, u64> = BTreeMap::new();
+        let mut lx: BTreeMap<u64, u64> = BTreeMap::new();
+        let mut cum_bases: u64 = 0;
+        let mut sum_sq: u64 = 0;
+        let mut next_stop = 0;
+        for (count, &length) in self.read_lengths.iter().enumerate() {
+            let length = length as u64;
+            cum_bases += length;
+            sum_sq += length * length;
+            while next_stop < stops.len() && cum_bases >= stops[next_stop].1 {
+                let (x, _) = stops[next_stop];
+                nx.insert(x, length);
+                lx.insert(x, count as u64 + 1);
+                next_stop += 1;
+            }
+        }
+
+        let aun = if total_bases > 0 {
+            sum_sq as f64 / total_bases as f64
+        } else {
+            0.0
+        };
+
+        (nx, lx, aun)
+    }
+    /// Get NGx contiguity metrics for several percentages in one pass
+    ///
+    /// Like [`ReadSet::nx_lx_aun`], but stops are computed against a supplied
+    /// `genome_size` rather than the total bases sequenced.
+    ///
+    /// # Example
+    ///
+    /// ```compile
+    /// let ngx = read_set.ngx_values(&[50], 2000);
+    /// let expected_ng50 = 1000;
+    /// assert_eq!(ngx[&50], expected_ng50);
+    /// ```
+    pub fn ngx_values(&mut self, percentages: &[u64], genome_size: u64) -> BTreeMap<u64, u64> {
+        self.read_lengths.sort_unstable();
+        self.read_lengths.reverse();
+
+        let mut stops: Vec<(u64, u64)> = percentages
+            .iter()
+            .map(|&x| {
+                let stop = (genome_size as f64 * x as f64 / 100.0).ceil() as u64;
+                (x, stop)
+            })
+            .collect();
+        stops.sort_by_key(|&(_, stop)| stop);
+
+        let mut ngx: BTreeMap<u64, u64> = BTreeMap::new();
+        let mut cum_bases: u64 = 0;
+        let mut next_stop = 0;
+        for &length in self.read_lengths.iter() {
+            let length = length as u64;
+            cum_bases += length;
+            while next_stop < stops.len() && cum_bases >= stops[next_stop].1 {
+                let (x, _) = stops[next_stop];
+                ngx.insert(x, length);
+                next_stop += 1;
+            }
+        }
+
+        ngx
     }
     /// Get the mean of read qualities
     ///
@@ -556,71 +945,133 @@ impl ReadSet {
             f32::NAN
         }
     }
+    /// Get read lengths at the given percentiles (0-100)
+    ///
+    /// # Example
+    ///
+    /// ```compile
+    /// let actual = read_set.length_percentiles(&[50]);
+    /// let expected = BTreeMap::from([(50, 100)]);
+    /// assert_eq!(actual, expected);
+    /// ```
+    pub fn length_percentiles(&mut self, percentiles: &[u64]) -> BTreeMap<u64, u32> {
+        self.read_lengths.sort_unstable();
+        percentiles
+            .iter()
+            .map(|&p| (p, exact_length_percentile(&self.read_lengths, p)))
+            .collect()
+    }
+    /// Get read qualities at the given percentiles (0-100)
+    ///
+    /// # Example
+    ///
+    /// ```compile
+    /// let actual = read_set.quality_percentiles(&[50]);
+    /// let expected = BTreeMap::from([(50, 11.0)]);
+    /// assert_eq!(actual, expected);
+    /// ```
+    pub fn quality_percentiles(&mut self, percentiles: &[u64]) -> BTreeMap<u64, f32> {
+        self.read_qualities
+            .sort_by(|a, b| a.partial_cmp(b).unwrap());
+        percentiles
+            .iter()
+            .map(|&p| (p, exact_quality_percentile(&self.read_qualities, p)))
+            .collect()
+    }
+    /// Get the population variance of read lengths
+    pub fn length_variance(&self) -> f64 {
+        let n = self.reads();
+        if n == 0 {
+            return 0.0;
+        }
+        let mean = self.bases() as f64 / n as f64;
+        let sum_sq_diff: f64 = self
+            .read_lengths
+            .iter()
+            .map(|&l| {
+                let diff = l as f64 - mean;
+                diff * diff
+            })
+            .sum();
+        sum_sq_diff / n as f64
+    }
+    /// Get the population standard deviation of read lengths
+    pub fn length_stddev(&self) -> f64 {
+        self.length_variance().sqrt()
+    }
+    /// Get the population variance of read qualities, `NaN` if empty
+    pub fn quality_variance(&self) -> f64 {
+        if self.read_qualities.is_empty() {
+            return f64::NAN;
+        }
+        let n = self.read_qualities.len() as f64;
+        let mean = self.read_qualities.iter().map(|&q| q as f64).sum::<f64>() / n;
+        let sum_sq_diff: f64 = self
+            .read_qualities
+            .iter()
+            .map(|&q| {
+                let diff = q as f64 - mean;
+                diff * diff
+            })
+            .sum();
+        sum_sq_diff / n
+    }
+    /// Get the population standard deviation of read qualities, `NaN` if empty
+    pub fn quality_stddev(&self) -> f64 {
+        self.quality_variance().sqrt()
+    }
 }
 
-/// Count reads at defined length and quality thresholds
+/// Get the read length at percentile `p` (0-100) from an already-sorted slice
 ///
-/// Used internally by the `print_thresholds` method.
+/// Uses the nearest-rank method (`ceil(p/100 * n)`-th smallest value),
+/// consistent with [`length_histogram_percentile`]'s target-rank approach.
+fn exact_length_percentile(sorted: &[u32], p: u64) -> u32 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = ((p as f64 / 100.0) * sorted.len() as f64).ceil().max(1.0) as usize;
+    sorted[rank.min(sorted.len()) - 1]
+}
+
+/// Get the read quality at percentile `p` (0-100) from an already-sorted slice
+///
+/// Uses the nearest-rank method, consistent with
+/// [`quality_histogram_percentile`]'s target-rank approach.
+fn exact_quality_percentile(sorted: &[f32], p: u64) -> f32 {
+    if sorted.is_empty() {
+        return f32::NAN;
+    }
+    let rank = ((p as f64 / 100.0) * sorted.len() as f64).ceil().max(1.0) as usize;
+    sorted[rank.min(sorted.len()) - 1]
+}
+
+/// Count reads at caller-supplied length and quality thresholds
+///
+/// Used internally by `ReadSet::get_thresholds`. Unlike a fixed set of
+/// counter fields, the threshold edges (e.g. from `--length-thresholds`)
+/// are supplied at construction time, so the number and value of bins is
+/// no longer baked into the struct's shape.
 struct ThresholdCounter {
-    // read quality
-    q5: u64,
-    q7: u64,
-    q10: u64,
-    q12: u64,
-    q15: u64,
-    q20: u64,
-    q25: u64,
-    q30: u64,
-    // read length
-    l200: u64,
-    l500: u64,
-    l1000: u64,
-    l2000: u64,
-    l5000: u64,
-    l10000: u64,
-    l30000: u64,
-    l50000: u64,
-    l100000: u64,
-    l1000000: u64,
+    length_edges: Vec<u64>,
+    quality_edges: Vec<u64>,
 }
 
 impl ThresholdCounter {
-    /// Create a new threshold counter
-    ///
-    /// Creates an instance of `ThresholdCounter`
-    /// with internal threshold counts set to zero.
+    /// Create a new threshold counter for the given length/quality edges
     ///
     /// # Example
     ///
     /// ```rust
-    /// let mut counter = ThresholdCounter::new();
+    /// let counter = ThresholdCounter::with_thresholds(vec![200, 500], vec![5, 10]);
     /// ```
-    fn new() -> Self {
+    fn with_thresholds(length_edges: Vec<u64>, quality_edges: Vec<u64>) -> Self {
         ThresholdCounter {
-            q5: 0,
-            q7: 0,
-            q10: 0,
-            q12: 0,
-            q15: 0,
-            q20: 0,
-            q25: 0,
-            q30: 0,
-            l200: 0,
-            l500: 0,
-            l1000: 0,
-            l2000: 0,
-            l5000: 0,
-            l10000: 0,
-            l30000: 0,
-            l50000: 0,
-            l100000: 0,
-            l1000000: 0,
-        }
-    }
-    /// Get read quality threshold counts
-    ///
-    /// Returns a tuple of counts for eight
-    /// average read quality thresholds (>=)
+            length_edges,
+            quality_edges,
+        }
+    }
+    /// Get read quality threshold counts (> edge)
     ///
     /// * `read_qualities`: a vector of read qualities
     ///     obtained from the `NeedleCast` methods
@@ -629,52 +1080,24 @@ impl ThresholdCounter {
     /// # Example
     ///
     /// ```rust
-    /// let mut counter = ThresholdCounter::new();
-    /// let expected = [2, 1, 0, 0, 0, 0, 0, 0];
+    /// let counter = ThresholdCounter::with_thresholds(vec![], vec![5, 10]);
+    /// let expected = BTreeMap::from([(5, 2), (10, 0)]);
     /// let actual = counter.quality(&vec![5.0, 7.0, 10.0]);
     /// assert_eq!(actual, expected);
     /// ```
-    fn quality(&mut self, read_qualities: &[f32]) -> BTreeMap<u64, u64> {
-        for q in read_qualities.iter() {
-            if q > &5.0 {
-                self.q5 += 1
-            }
-            if q > &7.0 {
-                self.q7 += 1
-            }
-            if q > &10.0 {
-                self.q10 += 1
-            }
-            if q > &12.0 {
-                self.q12 += 1
-            }
-            if q > &15.0 {
-                self.q15 += 1
-            }
-            if q > &20.0 {
-                self.q20 += 1
-            }
-            if q > &25.0 {
-                self.q25 += 1
-            }
-            if q > &30.0 {
-                self.q30 += 1
-            }
-        }
-        let read_counts = [
-            self.q5, self.q7, self.q10, self.q12, self.q15, self.q20, self.q25, self.q30,
-        ];
-
-        QUALITY_THRESHOLDS
+    fn quality(&self, read_qualities: &[f32]) -> BTreeMap<u64, u64> {
+        self.quality_edges
             .iter()
-            .copied()
-            .zip(read_counts.iter().copied())
+            .map(|&edge| {
+                let count = read_qualities
+                    .iter()
+                    .filter(|&&q| q > edge as f32)
+                    .count() as u64;
+                (edge, count)
+            })
             .collect()
     }
-    /// Get read length threshold counts
-    ///
-    /// Returns a tuple of counts for ten
-    /// read length thresholds (>=)
+    /// Get read length threshold counts (> edge)
     ///
     /// * `read_lengths`: a vector of read lengths
     ///     obtained from the `NeedleCast` methods
@@ -683,65 +1106,340 @@ impl ThresholdCounter {
     /// # Example
     ///
     /// ```rust
-    /// let mut counter = ThresholdCounter::new();
-    /// let expected = (2, 1, 0, 0, 0, 0, 0, 0, 0, 0);
+    /// let counter = ThresholdCounter::with_thresholds(vec![200, 500], vec![]);
+    /// let expected = BTreeMap::from([(200, 2), (500, 1)]);
     /// let actual = counter.length(&vec![200, 500, 1000]);
     /// assert_eq!(actual, expected);
     /// ```
-    fn length(&mut self, read_lengths: &[u32]) -> BTreeMap<u64, u64> {
-        for l in read_lengths.iter() {
-            if l > &200 {
-                self.l200 += 1
-            }
-            if l > &500 {
-                self.l500 += 1
-            }
-            if l > &1000 {
-                self.l1000 += 1
-            }
-            if l > &2000 {
-                self.l2000 += 1
-            }
-            if l > &5000 {
-                self.l5000 += 1
-            }
-            if l > &10000 {
-                self.l10000 += 1
-            }
-            if l > &30000 {
-                self.l30000 += 1
-            }
-            if l > &50000 {
-                self.l50000 += 1
+    fn length(&self, read_lengths: &[u32]) -> BTreeMap<u64, u64> {
+        self.length_edges
+            .iter()
+            .map(|&edge| {
+                let count = read_lengths
+                    .iter()
+                    .filter(|&&l| l as u64 > edge)
+                    .count() as u64;
+                (edge, count)
+            })
+            .collect()
+    }
+}
+
+/// Number of log-spaced length bins; bin `i` covers read lengths `[2^i, 2^(i+1))`
+const STREAM_LENGTH_BINS: usize = 32;
+/// Width (Q) of each streaming quality bin
+const STREAM_QUALITY_BIN_WIDTH: f32 = 0.1;
+/// Number of fixed-width quality bins, covering Q 0.0 up to 100.0
+const STREAM_QUALITY_BINS: usize = 1000;
+
+/// Constant-memory accumulator for read length/quality statistics
+///
+/// Unlike `ReadSet`, which stores every read length and quality and sorts
+/// them to compute `median_length`/`median_quality`/`n50`, this increments
+/// counts in fixed bins as reads stream in, bounding memory use regardless
+/// of the number of reads. Length bins are log-spaced (read lengths span
+/// several orders of magnitude); quality bins are fixed-width (0.1 Q, since
+/// qualities are bounded in range). Percentiles/N50 computed from these
+/// histograms are therefore approximate, accurate to within one bin width.
+#[derive(Debug)]
+pub struct StreamingReadSet {
+    length_counts: [u64; STREAM_LENGTH_BINS],
+    quality_counts: [u64; STREAM_QUALITY_BINS],
+    reads: u64,
+    bases: u64,
+    quality_reads: u64,
+}
+
+impl Default for StreamingReadSet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StreamingReadSet {
+    pub fn new() -> Self {
+        StreamingReadSet {
+            length_counts: [0; STREAM_LENGTH_BINS],
+            quality_counts: [0; STREAM_QUALITY_BINS],
+            reads: 0,
+            bases: 0,
+            quality_reads: 0,
+        }
+    }
+    /// Bin a single read's length and, if available, its mean quality
+    ///
+    /// NaN qualities are dropped rather than binned, mirroring `ReadSet`
+    /// treating an empty quality vector as "no quality data".
+    pub fn add(&mut self, length: u32, quality: Option<f32>) {
+        self.length_counts[length_bin(length)] += 1;
+        self.reads += 1;
+        self.bases += length as u64;
+        if let Some(q) = quality {
+            if !q.is_nan() {
+                self.quality_counts[quality_bin(q)] += 1;
+                self.quality_reads += 1;
             }
-            if l > &100000 {
-                self.l100000 += 1
+        }
+    }
+    pub fn reads(&self) -> u64 {
+        self.reads
+    }
+    pub fn bases(&self) -> u64 {
+        self.bases
+    }
+    /// Approximate N50
+    ///
+    /// Walks bins from the longest length downward, accumulating
+    /// bin-midpoint * count "bases" until half the total bases have been
+    /// seen, then reports that bin's representative length.
+    pub fn n50(&self) -> u64 {
+        if self.bases == 0 {
+            return 0;
+        }
+        let stop = self.bases / 2;
+        let mut cum_bases: u64 = 0;
+        for (bin, &count) in self.length_counts.iter().enumerate().rev() {
+            if count == 0 {
+                continue;
             }
-            if l > &1000000 {
-                self.l1000000 += 1
+            let midpoint = length_bin_midpoint(bin);
+            cum_bases += midpoint * count;
+            if cum_bases >= stop {
+                return midpoint;
             }
         }
-        let read_counts = [
-            self.l200,
-            self.l500,
-            self.l1000,
-            self.l2000,
-            self.l5000,
-            self.l10000,
-            self.l30000,
-            self.l50000,
-            self.l100000,
-            self.l1000000,
-        ];
-
-        LENGTH_THRESHOLDS
+        0
+    }
+    /// Approximate percentile (0.0-100.0) of read length
+    pub fn length_percentile(&self, p: f64) -> u32 {
+        length_histogram_percentile(&self.length_counts, self.reads, p)
+    }
+    pub fn median_length(&self) -> u32 {
+        self.length_percentile(50.0)
+    }
+    /// Approximate percentile (0.0-100.0) of read quality
+    pub fn quality_percentile(&self, p: f64) -> f32 {
+        quality_histogram_percentile(&self.quality_counts, self.quality_reads, p)
+    }
+    pub fn median_quality(&self) -> f32 {
+        self.quality_percentile(50.0)
+    }
+    /// Read counts at the given length thresholds (>), read directly off
+    /// the same histogram used for percentiles/N50
+    pub fn length_thresholds(&self, thresholds: &[u64]) -> BTreeMap<u64, u64> {
+        thresholds
             .iter()
-            .copied()
-            .zip(read_counts.iter().copied())
+            .map(|&threshold| {
+                let count: u64 = self
+                    .length_counts
+                    .iter()
+                    .enumerate()
+                    .filter(|&(bin, _)| length_bin_midpoint(bin) > threshold)
+                    .map(|(_, &count)| count)
+                    .sum();
+                (threshold, count)
+            })
             .collect()
     }
 }
 
+fn length_bin(length: u32) -> usize {
+    if length == 0 {
+        0
+    } else {
+        (31 - length.leading_zeros() as usize).min(STREAM_LENGTH_BINS - 1)
+    }
+}
+
+fn length_bin_edges(bin: usize) -> (u64, u64) {
+    (1u64 << bin, 1u64 << (bin + 1))
+}
+
+fn length_bin_midpoint(bin: usize) -> u64 {
+    let (lower, upper) = length_bin_edges(bin);
+    (lower + upper) / 2
+}
+
+fn quality_bin(quality: f32) -> usize {
+    let bin = (quality.max(0.0) / STREAM_QUALITY_BIN_WIDTH) as usize;
+    bin.min(STREAM_QUALITY_BINS - 1)
+}
+
+fn quality_bin_edges(bin: usize) -> (f32, f32) {
+    (
+        bin as f32 * STREAM_QUALITY_BIN_WIDTH,
+        (bin + 1) as f32 * STREAM_QUALITY_BIN_WIDTH,
+    )
+}
+
+/// Approximate percentile from a length histogram
+///
+/// Finds the target rank `ceil(p/100 * total)`, walks bins accumulating
+/// counts until the target rank is reached, then linearly interpolates
+/// within that bin's edges.
+fn length_histogram_percentile(counts: &[u64], total: u64, p: f64) -> u32 {
+    if total == 0 {
+        return 0;
+    }
+    let target = ((p / 100.0) * total as f64).ceil().max(1.0) as u64;
+    let mut cum: u64 = 0;
+    for (bin, &count) in counts.iter().enumerate() {
+        if count == 0 {
+            continue;
+        }
+        let previous_cum = cum;
+        cum += count;
+        if cum >= target {
+            let (lower, upper) = length_bin_edges(bin);
+            let within = (target - previous_cum) as f64 / count as f64;
+            return (lower as f64 + within * (upper - lower) as f64) as u32;
+        }
+    }
+    0
+}
+
+/// Approximate percentile from a quality histogram, analogous to
+/// [`length_histogram_percentile`]
+fn quality_histogram_percentile(counts: &[u64], total: u64, p: f64) -> f32 {
+    if total == 0 {
+        return f32::NAN;
+    }
+    let target = ((p / 100.0) * total as f64).ceil().max(1.0) as u64;
+    let mut cum: u64 = 0;
+    for (bin, &count) in counts.iter().enumerate() {
+        if count == 0 {
+            continue;
+        }
+        let previous_cum = cum;
+        cum += count;
+        if cum >= target {
+            let (lower, upper) = quality_bin_edges(bin);
+            let within = (target - previous_cum) as f64 / count as f64;
+            return lower + within as f32 * (upper - lower);
+        }
+    }
+    f32::NAN
+}
+
+/// Online P² quantile estimator (Jain & Chlamtac, 1985)
+///
+/// Estimates a single quantile `p` in O(1) memory by maintaining five
+/// markers (a height and an integer position each) and nudging them
+/// toward their ideal positions as observations arrive, rather than
+/// retaining every observation the way `ReadSet` does. This coexists with
+/// `ReadSet`'s exact sort-based percentiles and `StreamingReadSet`'s
+/// binned histograms as a third, constant-memory option.
+pub struct P2Estimator {
+    p: f64,
+    initial: Vec<f64>,
+    heights: [f64; 5],
+    positions: [i64; 5],
+    desired_positions: [f64; 5],
+    increments: [f64; 5],
+    initialized: bool,
+}
+
+impl P2Estimator {
+    /// Create a new estimator for quantile `p` (0.0-1.0, e.g. 0.5 for median)
+    pub fn new(p: f64) -> Self {
+        P2Estimator {
+            p,
+            initial: Vec::with_capacity(5),
+            heights: [0.0; 5],
+            positions: [0; 5],
+            desired_positions: [0.0; 5],
+            increments: [0.0; 5],
+            initialized: false,
+        }
+    }
+    /// Feed a single observation into the estimator
+    pub fn add(&mut self, x: f64) {
+        if !self.initialized {
+            self.initial.push(x);
+            if self.initial.len() == 5 {
+                self.initial.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                self.heights.copy_from_slice(&self.initial);
+                self.positions = [1, 2, 3, 4, 5];
+                let p = self.p;
+                self.desired_positions = [1.0, 1.0 + 2.0 * p, 1.0 + 4.0 * p, 3.0 + 2.0 * p, 5.0];
+                self.increments = [0.0, p / 2.0, p, (1.0 + p) / 2.0, 1.0];
+                self.initialized = true;
+            }
+            return;
+        }
+
+        let k = if x < self.heights[0] {
+            self.heights[0] = x;
+            0
+        } else if x >= self.heights[4] {
+            self.heights[4] = x;
+            3
+        } else {
+            (0..4)
+                .find(|&i| self.heights[i] <= x && x < self.heights[i + 1])
+                .unwrap_or(3)
+        };
+
+        for position in self.positions.iter_mut().skip(k + 1) {
+            *position += 1;
+        }
+        for i in 0..5 {
+            self.desired_positions[i] += self.increments[i];
+        }
+
+        for i in 1..4 {
+            let d = self.desired_positions[i] - self.positions[i] as f64;
+            let right_gap = self.positions[i + 1] - self.positions[i];
+            let left_gap = self.positions[i - 1] - self.positions[i];
+
+            if (d >= 1.0 && right_gap > 1) || (d <= -1.0 && left_gap < -1) {
+                let d_sign: i64 = if d >= 1.0 { 1 } else { -1 };
+                let parabolic = self.parabolic_height(i, d_sign);
+                self.heights[i] = if self.heights[i - 1] < parabolic && parabolic < self.heights[i + 1] {
+                    parabolic
+                } else {
+                    self.linear_height(i, d_sign)
+                };
+                self.positions[i] += d_sign;
+            }
+        }
+    }
+    /// Current estimate of the p-th quantile
+    ///
+    /// Before five observations have been seen, falls back to the median
+    /// of however many values have been buffered so far.
+    pub fn estimate(&self) -> f64 {
+        if self.initialized {
+            self.heights[2]
+        } else if self.initial.is_empty() {
+            f64::NAN
+        } else {
+            let mut sorted = self.initial.clone();
+            sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            sorted[sorted.len() / 2]
+        }
+    }
+    fn parabolic_height(&self, i: usize, d_sign: i64) -> f64 {
+        let (n_im1, n_i, n_ip1) = (
+            self.positions[i - 1] as f64,
+            self.positions[i] as f64,
+            self.positions[i + 1] as f64,
+        );
+        let (q_im1, q_i, q_ip1) = (self.heights[i - 1], self.heights[i], self.heights[i + 1]);
+        let d = d_sign as f64;
+        q_i + d / (n_ip1 - n_im1)
+            * ((n_i - n_im1 + d) * (q_ip1 - q_i) / (n_ip1 - n_i)
+                + (n_ip1 - n_i - d) * (q_i - q_im1) / (n_i - n_im1))
+    }
+    fn linear_height(&self, i: usize, d_sign: i64) -> f64 {
+        let neighbor = (i as i64 + d_sign) as usize;
+        let d = d_sign as f64;
+        self.heights[i]
+            + d * (self.heights[neighbor] - self.heights[i])
+                / (self.positions[neighbor] as f64 - self.positions[i] as f64)
+    }
+}
+
 // utility function to get length threshold percent
 fn get_length_percent(number: u64, n_reads: u64) -> f64 {
     (number as f64 / n_reads as f64) * 100.0
@@ -752,6 +1450,60 @@ fn get_quality_percent(number: u64, n_reads: u64) -> f64 {
     (number as f64 / n_reads as f64) * 100.0
 }
 
+/// Wraps an `f32` so it can be held in a `BinaryHeap`, which requires `Ord`
+///
+/// Read qualities are always finite (computed from Phred scores), so
+/// `partial_cmp` is expected to succeed.
+#[derive(PartialEq, PartialOrd)]
+struct OrderedF32(f32);
+
+impl Eq for OrderedF32 {}
+
+impl Ord for OrderedF32 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.partial_cmp(&other.0).unwrap()
+    }
+}
+
+/// Select the `top` largest read lengths with a capacity-`top` min-heap
+fn top_k_lengths(read_lengths: &[u32], top: usize) -> Vec<u32> {
+    let mut heap: BinaryHeap<Reverse<u32>> = BinaryHeap::with_capacity(top);
+    for &length in read_lengths {
+        if heap.len() < top {
+            heap.push(Reverse(length));
+        } else if let Some(&Reverse(min)) = heap.peek() {
+            if length > min {
+                heap.pop();
+                heap.push(Reverse(length));
+            }
+        }
+    }
+    let mut top_lengths: Vec<u32> = heap.into_iter().map(|Reverse(length)| length).collect();
+    top_lengths.sort_unstable_by(|a, b| b.cmp(a));
+    top_lengths
+}
+
+/// Select the `top` largest read qualities with a capacity-`top` min-heap
+fn top_k_qualities(read_qualities: &[f32], top: usize) -> Vec<f32> {
+    let mut heap: BinaryHeap<Reverse<OrderedF32>> = BinaryHeap::with_capacity(top);
+    for &quality in read_qualities {
+        if heap.len() < top {
+            heap.push(Reverse(OrderedF32(quality)));
+        } else if let Some(&Reverse(OrderedF32(min))) = heap.peek() {
+            if quality > min {
+                heap.pop();
+                heap.push(Reverse(OrderedF32(quality)));
+            }
+        }
+    }
+    let mut top_qualities: Vec<f32> = heap
+        .into_iter()
+        .map(|Reverse(OrderedF32(quality))| quality)
+        .collect();
+    top_qualities.sort_by(|a, b| b.partial_cmp(a).unwrap());
+    top_qualities
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -777,11 +1529,267 @@ mod tests {
             niffler::Format::from_path("baz.fq.lzma"),
             niffler::Format::Lzma
         );
+        assert_eq!(
+            niffler::Format::from_path("baz.fq.xz"),
+            niffler::Format::Lzma
+        );
+    }
+
+    #[test]
+    fn get_ranking_returns_top_k_without_mutating_read_set() {
+        let read_set = ReadSet::new(vec![10, 50, 30, 20, 40], vec![1.0, 5.0, 3.0, 2.0, 4.0]);
+
+        let (top_lengths, top_qualities) = read_set.get_ranking(3);
+
+        assert_eq!(top_lengths, vec![50, 40, 30]);
+        assert_eq!(top_qualities, vec![5.0, 4.0, 3.0]);
+        // underlying vectors are left in their original order
+        assert_eq!(read_set.read_lengths, vec![10, 50, 30, 20, 40]);
+        assert_eq!(read_set.read_qualities, vec![1.0, 5.0, 3.0, 2.0, 4.0]);
+    }
+
+    #[test]
+    fn get_ranking_caps_at_available_reads() {
+        let read_set = ReadSet::new(vec![10, 20], vec![]);
+
+        let (top_lengths, top_qualities) = read_set.get_ranking(5);
+
+        assert_eq!(top_lengths, vec![20, 10]);
+        assert_eq!(top_qualities, Vec::<f32>::new());
+    }
+
+    #[test]
+    fn streaming_read_set_approximates_exact_stats() {
+        let lengths: Vec<u32> = vec![10, 1000, 100, 500, 2000, 1500, 300, 50000];
+        let qualities: Vec<f32> = vec![10.0, 12.0, 8.0, 20.0, 15.0, 9.0, 30.0, 5.0];
+
+        let mut exact = ReadSet::new(lengths.clone(), qualities.clone());
+        let mut stream = StreamingReadSet::new();
+        for (&length, &quality) in lengths.iter().zip(qualities.iter()) {
+            stream.add(length, Some(quality));
+        }
+
+        assert_eq!(stream.reads(), exact.reads());
+        assert_eq!(stream.bases(), exact.bases());
+
+        // bin-approximate, so assert closeness rather than equality
+        let exact_n50 = exact.n50();
+        assert!((stream.n50() as i64 - exact_n50 as i64).unsigned_abs() <= exact_n50);
+
+        let exact_median = exact.median_length() as i64;
+        assert!((stream.median_length() as i64 - exact_median).abs() <= exact_median.max(1));
+    }
+
+    #[test]
+    fn streaming_read_set_empty_is_zero() {
+        let stream = StreamingReadSet::new();
+        assert_eq!(stream.reads(), 0);
+        assert_eq!(stream.bases(), 0);
+        assert_eq!(stream.n50(), 0);
+        assert_eq!(stream.median_length(), 0);
+        assert!(stream.median_quality().is_nan());
+    }
+
+    #[test]
+    fn streaming_read_set_length_thresholds_match_histogram() {
+        let mut stream = StreamingReadSet::new();
+        for length in [100, 600, 1500, 6000] {
+            stream.add(length, None);
+        }
+        let thresholds = stream.length_thresholds(&[500, 5000]);
+        assert_eq!(thresholds.get(&500), Some(&3));
+        assert_eq!(thresholds.get(&5000), Some(&1));
+    }
+
+    #[test]
+    fn p2_estimator_approximates_exact_median() {
+        let lengths: Vec<f64> = vec![
+            10.0, 1000.0, 100.0, 500.0, 2000.0, 1500.0, 300.0, 50000.0, 20.0, 4000.0, 250.0,
+        ];
+
+        let mut read_set = ReadSet::new(
+            lengths.iter().map(|&l| l as u32).collect(),
+            vec![],
+        );
+        let exact_median = read_set.median_length() as f64;
+
+        let mut estimator = P2Estimator::new(0.5);
+        for &length in &lengths {
+            estimator.add(length);
+        }
+
+        let estimate = estimator.estimate();
+        // bin-free but still an approximation once markers start adjusting
+        assert!((estimate - exact_median).abs() <= exact_median.max(1.0));
+    }
+
+    #[test]
+    fn p2_estimator_before_five_observations_falls_back_to_buffered_median() {
+        let mut estimator = P2Estimator::new(0.5);
+        assert!(estimator.estimate().is_nan());
+
+        estimator.add(10.0);
+        estimator.add(30.0);
+        estimator.add(20.0);
+
+        assert_eq!(estimator.estimate(), 20.0);
+    }
+
+    #[test]
+    fn summary_round_trips_through_json() {
+        let mut read_set = ReadSet::new(vec![10, 100, 1000], vec![10.0, 11.0, 12.0]);
+
+        let length_range = read_set.range_length();
+        let (length_thresholds, quality_thresholds) = read_set.get_thresholds(&[], &[]);
+        let (length_percentiles, quality_percentiles) = read_set.get_percentiles(&[]);
+        let (top_lengths, top_qualities) = read_set.get_ranking(5);
+        let (nx, lx, aun) = read_set.nx_lx_aun(&NX_PERCENTAGES);
+
+        let output_data = OutputData {
+            reads: read_set.reads(),
+            bases: read_set.bases(),
+            n50: read_set.n50(),
+            longest: length_range[1],
+            shortest: length_range[0],
+            mean_length: read_set.mean_length(),
+            median_length: read_set.median_length(),
+            mean_quality: read_set.mean_quality(),
+            median_quality: read_set.median_quality(),
+            length_variance: read_set.length_variance(),
+            length_stddev: read_set.length_stddev(),
+            quality_variance: read_set.quality_variance(),
+            quality_stddev: read_set.quality_stddev(),
+            length_thresholds,
+            quality_thresholds,
+            length_percentiles,
+            quality_percentiles,
+            top_lengths,
+            top_qualities,
+            filtered: 0,
+            nx,
+            lx,
+            aun,
+            ngx: BTreeMap::new(),
+        };
+
+        let summary = Summary::from(&output_data);
+        assert_eq!(summary.schema_version, SCHEMA_VERSION);
+
+        let json = serde_json::to_string(&summary).expect("summary should serialize");
+        let roundtripped: Summary =
+            serde_json::from_str(&json).expect("summary should deserialize");
+
+        assert_eq!(summary, roundtripped);
+    }
+
+    #[test]
+    fn nx_lx_ngx_match_known_vectors() {
+        // descending lengths: 600, 300, 100; bases: 1000
+        let mut read_set = ReadSet::new(vec![100, 300, 600], vec![]);
+
+        assert_eq!(read_set.nx(50.0), 600);
+        assert_eq!(read_set.lx(50.0), 1);
+        assert_eq!(read_set.n50(), read_set.nx(50.0));
+
+        // genome_size = 2000 -> NG50 stop = 1000, only reached after all reads
+        assert_eq!(read_set.ngx(50.0, 2000), 100);
+        // genome_size = 600 -> NG50 stop = 300, reached at the first (longest) read
+        assert_eq!(read_set.ngx(50.0, 600), 600);
+    }
+
+    #[test]
+    fn nx_lx_ngx_of_empty_read_set_are_zero() {
+        let mut read_set = ReadSet::new(vec![], vec![]);
+        assert_eq!(read_set.nx(50.0), 0);
+        assert_eq!(read_set.lx(50.0), 0);
+        assert_eq!(read_set.ngx(50.0, 1000), 0);
+    }
+
+    #[test]
+    fn ngx_values_match_scalar_ngx() {
+        let mut read_set = ReadSet::new(vec![100, 300, 600], vec![]);
+        let ngx = read_set.ngx_values(&[50], 600);
+        assert_eq!(ngx[&50], read_set.ngx(50.0, 600));
+    }
+
+    #[test]
+    fn nx_lx_aun_match_n50_and_expected_auN() {
+        use float_eq::float_eq;
+
+        // bases: 100 + 300 + 600 = 1000, descending lengths: 600, 300, 100
+        let mut read_set = ReadSet::new(vec![100, 300, 600], vec![]);
+        let n50 = read_set.n50();
+
+        let (nx, lx, aun) = read_set.nx_lx_aun(&[50]);
+
+        assert_eq!(nx[&50], n50);
+        assert_eq!(lx[&50], 1);
+
+        let expected_aun = (600 * 600 + 300 * 300 + 100 * 100) as f64 / 1000.0;
+        float_eq!(aun, expected_aun, abs <= 1e-9);
+    }
+
+    #[test]
+    fn nx_lx_aun_empty_is_zero() {
+        let mut read_set = ReadSet::new(vec![], vec![]);
+        let (nx, lx, aun) = read_set.nx_lx_aun(&[10, 50, 90]);
+
+        assert!(nx.is_empty());
+        assert!(lx.is_empty());
+        assert_eq!(aun, 0.0);
+    }
+
+    #[test]
+    fn length_and_quality_percentiles_use_nearest_rank() {
+        let mut read_set = ReadSet::new(
+            vec![10, 20, 30, 40, 50, 60, 70, 80, 90, 100],
+            vec![1.0, 2.0, 3.0, 4.0, 5.0],
+        );
+
+        let length_percentiles = read_set.length_percentiles(&[10, 50, 90, 100]);
+        assert_eq!(length_percentiles[&10], 10);
+        assert_eq!(length_percentiles[&50], 50);
+        assert_eq!(length_percentiles[&90], 90);
+        assert_eq!(length_percentiles[&100], 100);
+
+        let quality_percentiles = read_set.quality_percentiles(&[50]);
+        assert_eq!(quality_percentiles[&50], 3.0);
+    }
+
+    #[test]
+    fn percentiles_of_empty_read_set_are_defaults() {
+        let mut read_set = ReadSet::new(vec![], vec![]);
+
+        let length_percentiles = read_set.length_percentiles(&[50]);
+        assert_eq!(length_percentiles[&50], 0);
+
+        let quality_percentiles = read_set.quality_percentiles(&[50]);
+        assert!(quality_percentiles[&50].is_nan());
+    }
+
+    #[test]
+    fn length_variance_and_stddev_ok() {
+        use float_eq::float_eq;
+
+        // mean = 30, variance = ((10-30)^2 + (20-30)^2 + (30-30)^2 + (40-30)^2 + (50-30)^2) / 5 = 200
+        let read_set = ReadSet::new(vec![10, 20, 30, 40, 50], vec![]);
+
+        float_eq!(read_set.length_variance(), 200.0, abs <= 1e-9);
+        float_eq!(read_set.length_stddev(), 200.0_f64.sqrt(), abs <= 1e-9);
+        assert!(read_set.quality_variance().is_nan());
+        assert!(read_set.quality_stddev().is_nan());
+    }
+
+    #[test]
+    fn length_variance_of_empty_read_set_is_zero() {
+        let read_set = ReadSet::new(vec![], vec![]);
+        assert_eq!(read_set.length_variance(), 0.0);
+        assert_eq!(read_set.length_stddev(), 0.0);
     }
 
     #[test]
     fn threshold_counter_methods_ok() {
-        let mut counter = ThresholdCounter::new();
+        let counter = ThresholdCounter::with_thresholds(LENGTH_THRESHOLDS.to_vec(), QUALITY_THRESHOLDS.to_vec());
         let exp_qual = BTreeMap::from([
             (5, 8),
             (7, 7),
@@ -815,6 +1823,29 @@ mod tests {
         assert_eq!(actual_len, exp_len);
     }
 
+    #[test]
+    fn threshold_counter_with_custom_thresholds_ok() {
+        // e.g. a run targeting very long reads
+        let counter = ThresholdCounter::with_thresholds(
+            vec![50_000, 100_000, 200_000, 500_000, 1_000_000],
+            vec![10, 20],
+        );
+
+        let actual_len = counter.length(&[10_000, 60_000, 150_000, 250_000, 600_000, 1_200_000]);
+        let exp_len = BTreeMap::from([
+            (50_000, 5),
+            (100_000, 4),
+            (200_000, 3),
+            (500_000, 2),
+            (1_000_000, 1),
+        ]);
+        assert_eq!(actual_len, exp_len);
+
+        let actual_qual = counter.quality(&[5.0, 15.0, 25.0]);
+        let exp_qual = BTreeMap::from([(10, 2), (20, 1)]);
+        assert_eq!(actual_qual, exp_qual);
+    }
+
     #[test]
     fn percent_functions_ok() {
         use float_eq::float_eq;
@@ -847,16 +1878,16 @@ mod tests {
         float_eq!(read_set_odd.median_quality(), 11.0, abs <= f32::EPSILON);
 
         read_set_odd
-            .summary(&0, 5, false, false, false, None)
+            .summary(&0, 5, false, false, false, None, 0, &[], &[], &[], &[], None)
             .unwrap();
         read_set_odd
-            .summary(&1, 5, false, false, false, None)
+            .summary(&1, 5, false, false, false, None, 0, &[], &[], &[], &[], None)
             .unwrap();
         read_set_odd
-            .summary(&2, 5, false, false, false, None)
+            .summary(&2, 5, false, false, false, None, 0, &[], &[], &[], &[], None)
             .unwrap();
         read_set_odd
-            .summary(&3, 5, false, false, false, None)
+            .summary(&3, 5, false, false, false, None, 0, &[], &[], &[], &[], None)
             .unwrap();
     }
 
@@ -866,7 +1897,7 @@ mod tests {
         let mut read_set_odd = ReadSet::new(vec![10, 100, 1000], vec![10.0, 11.0, 12.0]);
 
         read_set_odd
-            .summary(&4, 5, false, false, false, None)
+            .summary(&4, 5, false, false, false, None, 0, &[], &[], &[], &[], None)
             .unwrap();
     }
 
@@ -878,7 +1909,7 @@ mod tests {
         assert!(read_set_noqual.median_quality().is_nan());
 
         read_set_noqual
-            .summary(&3, 5, false, false, false, None)
+            .summary(&3, 5, false, false, false, None, 0, &[], &[], &[], &[], None)
             .unwrap();
     }
 
@@ -892,7 +1923,7 @@ mod tests {
         assert_eq!(read_set_none.range_length(), [0, 0]);
 
         read_set_none
-            .summary(&3, 5, false, false, false, None)
+            .summary(&3, 5, false, false, false, None, 0, &[], &[], &[], &[], None)
             .unwrap();
     }
 
@@ -908,7 +1939,7 @@ mod tests {
         assert_eq!(read_set_none.range_length(), [10, 10]);
 
         read_set_none
-            .summary(&3, 5, false, false, false, None)
+            .summary(&3, 5, false, false, false, None, 0, &[], &[], &[], &[], None)
             .unwrap();
     }
     #[test]
@@ -923,21 +1954,21 @@ mod tests {
         assert_eq!(read_set_none.range_length(), [10, 10]);
 
         read_set_none
-            .summary(&3, 5, false, false, false, None)
+            .summary(&3, 5, false, false, false, None, 0, &[], &[], &[], &[], None)
             .unwrap();
     }
     #[test]
     fn summary_header_stderr_ok() {
         let mut read_set_none = ReadSet::new(vec![10], vec![8.0]);
         read_set_none
-            .summary(&0, 3, true, false, false, None)
+            .summary(&0, 3, true, false, false, None, 0, &[], &[], &[], &[], None)
             .unwrap();
     }
     #[test]
     fn summary_json_ok() {
         let mut read_set_none = ReadSet::new(vec![10], vec![8.0]);
         read_set_none
-            .summary(&0, 3, true, false, true, None)
+            .summary(&0, 3, true, false, true, None, 0, &[], &[], &[], &[], None)
             .unwrap();
     }
     #[test]
@@ -946,7 +1977,7 @@ mod tests {
 
         let sink_file = PathBuf::from("/dev/null");
         read_set_none
-            .summary(&0, 3, true, false, true, Some(sink_file))
+            .summary(&0, 3, true, false, true, Some(sink_file), 0, &[], &[], &[], &[], None)
             .unwrap();
     }
     #[test]
@@ -955,14 +1986,14 @@ mod tests {
 
         let sink_file = PathBuf::from("/dev/null");
         read_set_none
-            .summary(&0, 3, true, false, false, Some(sink_file))
+            .summary(&0, 3, true, false, false, Some(sink_file), 0, &[], &[], &[], &[], None)
             .unwrap();
     }
     #[test]
     fn summary_report_stats_ok() {
         let mut read_set_none = ReadSet::new(vec![10], vec![8.0]);
         read_set_none
-            .summary(&0, 1, true, true, true, None)
+            .summary(&0, 1, true, true, true, None, 0, &[], &[], &[], &[], None)
             .unwrap();
     }
 }
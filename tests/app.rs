@@ -7,7 +7,7 @@ use tempfile::tempdir;
 #[test]
 fn input_file_doesnt_exist() -> Result<(), Box<dyn std::error::Error>> {
     let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
-    cmd.args(vec!["-i", "file/doesnt/exist.fq", "-s"]);
+    cmd.args(vec!["-i", "file/doesnt/exist.fq", "stats", "-s"]);
     cmd.assert()
         .failure()
         .stderr(predicate::str::contains("No such file"));
@@ -15,6 +15,17 @@ fn input_file_doesnt_exist() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+#[test]
+fn invalid_size_suffix_fails() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.args(vec!["-i", "tests/cases/test_ok.fq", "filter", "-l", "5xb"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("not a valid size"));
+
+    Ok(())
+}
+
 #[test]
 fn output_file_in_nonexistant_dir() -> Result<(), Box<dyn std::error::Error>> {
     let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
@@ -23,6 +34,7 @@ fn output_file_in_nonexistant_dir() -> Result<(), Box<dyn std::error::Error>> {
         "tests/cases/test_ok.fq",
         "-o",
         "dir/doesnt/exists/out.fq",
+        "filter",
     ]);
     cmd.assert()
         .failure()
@@ -41,6 +53,7 @@ fn valid_inputs_raise_no_errors() -> Result<(), Box<dyn std::error::Error>> {
         "g",
         "-c",
         "9",
+        "filter",
         "-l",
         "5000",
     ]);
@@ -53,7 +66,7 @@ fn valid_inputs_raise_no_errors() -> Result<(), Box<dyn std::error::Error>> {
 #[test]
 fn valid_input_output_stdout_ok() -> Result<(), Box<dyn std::error::Error>> {
     let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
-    cmd.args(vec!["-i", "tests/cases/test_ok.fq"]);
+    cmd.args(vec!["-i", "tests/cases/test_ok.fq", "filter"]);
 
     cmd.assert().success();
 
@@ -71,6 +84,7 @@ fn valid_length_file_output() -> Result<(), Box<dyn std::error::Error>> {
     cmd.args(vec![
         "-i",
         "tests/cases/test_ok.fq",
+        "stats",
         "-L",
         test_file_path_str,
     ]);
@@ -84,6 +98,127 @@ fn valid_length_file_output() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+#[test]
+fn compressed_output_extension_roundtrip() -> Result<(), Box<dyn std::error::Error>> {
+    for extension in ["gz", "bz2", "xz"] {
+        let dir = tempdir()?;
+        let compressed = dir.path().join(format!("out.fq.{}", extension));
+
+        // No `-O` given: the codec is inferred from the output extension.
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+        cmd.args(vec![
+            "-i",
+            "tests/cases/test_ok.fq",
+            "-o",
+            compressed.to_str().unwrap(),
+            "filter",
+        ]);
+        cmd.assert().success();
+
+        let length_file = dir.path().join("length.txt");
+        let quality_file = dir.path().join("quality.txt");
+
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+        cmd.args(vec![
+            "-i",
+            compressed.to_str().unwrap(),
+            "stats",
+            "-L",
+            length_file.to_str().unwrap(),
+            "-Q",
+            quality_file.to_str().unwrap(),
+        ]);
+        cmd.assert().success();
+
+        assert_eq!(fs::read_to_string(&length_file)?.trim(), "4");
+        assert_eq!(fs::read_to_string(&quality_file)?.trim(), "40.0");
+
+        dir.close()?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn bgzf_output_roundtrip() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let compressed = dir.path().join("out.fq.gz");
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.args(vec![
+        "-i",
+        "tests/cases/test_ok.fq",
+        "-O",
+        "f",
+        "-j",
+        "2",
+        "-o",
+        compressed.to_str().unwrap(),
+        "filter",
+    ]);
+    cmd.assert().success();
+
+    // BGZF is a valid block-structured gzip stream, so niffler's magic-byte
+    // detection on the `stats` read path picks it up like any other `.gz` file.
+    let length_file = dir.path().join("length.txt");
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.args(vec![
+        "-i",
+        compressed.to_str().unwrap(),
+        "stats",
+        "-L",
+        length_file.to_str().unwrap(),
+    ]);
+    cmd.assert().success();
+
+    assert_eq!(fs::read_to_string(&length_file)?.trim(), "4");
+
+    dir.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn zstd_and_snappy_output_roundtrip() -> Result<(), Box<dyn std::error::Error>> {
+    for (flag, extension) in [("z", "zst"), ("s", "sz")] {
+        let dir = tempdir()?;
+        let compressed = dir.path().join(format!("out.fq.{}", extension));
+
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+        cmd.args(vec![
+            "-i",
+            "tests/cases/test_ok.fq",
+            "-O",
+            flag,
+            "-o",
+            compressed.to_str().unwrap(),
+            "filter",
+        ]);
+        cmd.assert().success();
+
+        // Reads back without `-O`: format is sniffed from the `.zst`/`.sz`
+        // extension and magic bytes, same as niffler's codecs.
+        let length_file = dir.path().join("length.txt");
+
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+        cmd.args(vec![
+            "-i",
+            compressed.to_str().unwrap(),
+            "stats",
+            "-L",
+            length_file.to_str().unwrap(),
+        ]);
+        cmd.assert().success();
+
+        assert_eq!(fs::read_to_string(&length_file)?.trim(), "4");
+
+        dir.close()?;
+    }
+
+    Ok(())
+}
+
 #[test]
 fn valid_quality_file_output() -> Result<(), Box<dyn std::error::Error>> {
     let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
@@ -95,6 +230,7 @@ fn valid_quality_file_output() -> Result<(), Box<dyn std::error::Error>> {
     cmd.args(vec![
         "-i",
         "tests/cases/test_ok.fq",
+        "stats",
         "-Q",
         test_file_path_str,
     ]);